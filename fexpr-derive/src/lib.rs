@@ -0,0 +1,167 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, LitStr};
+
+// Filterable derives `fexpr::filter::Filterable` and `fexpr::filter::Record`
+// for a struct with named fields.
+//
+// Each struct field is exposed under its name and could be customized with:
+//   - `#[filter(rename = "...")]` - exposes the field under a different name
+//   - `#[filter(skip)]` - doesn't expose the field at all
+//
+// All exposed field types must implement `fexpr::filter::Filterable`
+// (nested structs with `#[derive(Filterable)]` are resolved with dot separated paths)
+// and the generic type params of the struct are required to implement it too.
+// The struct itself is a non-null value that is not comparable as a whole.
+#[proc_macro_derive(Filterable, attributes(filter))]
+pub fn derive_filterable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "Filterable could be derived only for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Filterable could be derived only for structs",
+            ))
+        }
+    };
+
+    let mut names = Vec::new();
+    let mut idents = Vec::new();
+    let mut types = Vec::new();
+
+    for field in fields {
+        let ident = field.ident.clone().expect("named field");
+        let mut name = ident.to_string().trim_start_matches("r#").to_string();
+        let mut skip = false;
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("filter"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported filter attribute (expected `rename` or `skip`)"))
+                }
+            })?;
+        }
+
+        if skip {
+            continue;
+        }
+
+        if names.contains(&name) {
+            return Err(Error::new_spanned(
+                field,
+                format!("duplicated filter field name `{name}`"),
+            ));
+        }
+
+        names.push(name);
+        idents.push(ident);
+        types.push(field.ty.clone());
+    }
+
+    // bound only the generic type params (bounding the field types
+    // instead would overflow the trait resolution of the recursive structs)
+    let params: Vec<_> = input
+        .generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = input.generics.make_where_clause();
+    for param in &params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#param: ::fexpr::filter::Filterable));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::fexpr::filter::Filterable for #ident #ty_generics #where_clause {
+            fn filter_get(
+                &self,
+                path: &[::std::string::String],
+            ) -> ::std::option::Option<::fexpr::filter::Value<'_>> {
+                let (head, rest) = match path.split_first() {
+                    ::std::option::Option::Some(split) => split,
+                    ::std::option::Option::None => {
+                        return ::std::option::Option::Some(::fexpr::filter::Value::Composite)
+                    }
+                };
+
+                match head.as_str() {
+                    #(#names => ::fexpr::filter::Filterable::filter_get(&self.#idents, rest),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            fn filter_any(
+                &self,
+                path: &[::std::string::String],
+                predicate: &mut dyn ::std::ops::FnMut(&::fexpr::filter::Value) -> bool,
+            ) -> bool {
+                match path.split_first() {
+                    #(::std::option::Option::Some((head, rest)) if head == #names => {
+                        ::fexpr::filter::Filterable::filter_any(&self.#idents, rest, predicate)
+                    })*
+                    ::std::option::Option::Some(_) => predicate(&::fexpr::filter::Value::Null),
+                    ::std::option::Option::None => predicate(&::fexpr::filter::Value::Composite),
+                }
+            }
+
+            fn filter_type(
+                path: &[::std::string::String],
+            ) -> ::std::option::Option<::fexpr::schema::FieldType> {
+                let (head, rest) = path.split_first()?;
+
+                match head.as_str() {
+                    #(#names => <#types as ::fexpr::filter::Filterable>::filter_type(rest),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+
+        impl #impl_generics ::fexpr::filter::Record for #ident #ty_generics #where_clause {
+            fn get(
+                &self,
+                field: &::fexpr::filter::Field,
+            ) -> ::std::option::Option<::fexpr::filter::Value<'_>> {
+                ::fexpr::filter::Filterable::filter_get(self, field.segments())
+            }
+
+            fn any(
+                &self,
+                field: &::fexpr::filter::Field,
+                predicate: &mut dyn ::std::ops::FnMut(&::fexpr::filter::Value) -> bool,
+            ) -> bool {
+                ::fexpr::filter::Filterable::filter_any(self, field.segments(), predicate)
+            }
+        }
+    })
+}
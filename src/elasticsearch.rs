@@ -0,0 +1,394 @@
+use serde_json::{json, Value};
+
+use crate::{
+    error::{Error, ErrorKind},
+    json::value,
+    parser::{Expr, ExprGroupItem, ExprGroups},
+    scanner::{JoinOp, SignOp, Token},
+    span::Span,
+};
+
+// compile compiles the provided AST into an Elasticsearch (or OpenSearch)
+// Query DSL `bool` query.
+//
+// Every identifier is resolved through the `fields` mapping which returns
+// the document field name for the identifier or `None` if it is not allowed.
+// Each expression must compare a field with a literal (the operands are
+// swapped if the field is on the right side of a comparison).
+//
+// The `&&` joined items are combined in a `bool.filter` clause and the `||`
+// joined ones in a `bool.should` clause with `minimum_should_match: 1`.
+//
+// The sign operators are compiled as follows:
+//   - `=`, `?=` - `term` (or `must_not` `exists` for null)
+//   - `!=` - `must_not` `term` (or `exists` for null)
+//   - `<`, `<=`, `>`, `>=` (and their `?` variants) - `range` with `lt`, `lte`, `gt`, `gte`
+//   - `~`, `?~` - case insensitive `wildcard` (`*value*` with escaped wildcards)
+//   - `!~` - `must_not` `wildcard`
+//   - `in`, `!in` - `terms` and `must_not` `terms`
+//
+// Since Elasticsearch fields are natively multi-valued, the `Any*` operators
+// match if any of the field values matches. `?!=` and `?!~` (aka. at least
+// one value doesn't match) can't be expressed and result in an error.
+pub fn compile<F>(expr_groups: &ExprGroups, fields: F) -> Result<Value, Error>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    Compiler { fields }.compile_groups(expr_groups, Span::default())
+}
+
+// escape_wildcard escapes the `wildcard` query special characters.
+fn escape_wildcard(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('*', "\\*")
+        .replace('?', "\\?")
+}
+
+// filter combines the clauses in a `bool.filter` query
+// (a single clause is returned as it is).
+fn filter(mut clauses: Vec<Value>) -> Value {
+    if clauses.len() == 1 {
+        return clauses.remove(0);
+    }
+
+    json!({"bool": {"filter": clauses}})
+}
+
+fn must_not(clause: Value) -> Value {
+    json!({"bool": {"must_not": [clause]}})
+}
+
+struct Compiler<F> {
+    fields: F,
+}
+
+impl<F> Compiler<F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    fn compile_groups(&self, expr_groups: &ExprGroups, span: Span) -> Result<Value, Error> {
+        if expr_groups.is_empty() {
+            return Err(Error::new(ErrorKind::Empty, Token::None, span));
+        }
+
+        // `&&` binds tighter than `||`
+        let mut conjunctions: Vec<Vec<Value>> = Vec::new();
+        for (i, expr_group) in expr_groups.iter().enumerate() {
+            let clause = self.compile_item(&expr_group.item, expr_group.span)?;
+
+            match conjunctions.last_mut() {
+                Some(conjunction) if i == 0 || expr_group.join == JoinOp::And => {
+                    conjunction.push(clause)
+                }
+                _ => conjunctions.push(vec![clause]),
+            }
+        }
+
+        if conjunctions.len() == 1 {
+            return Ok(filter(conjunctions.remove(0)));
+        }
+
+        let should: Vec<Value> = conjunctions.into_iter().map(filter).collect();
+
+        Ok(json!({"bool": {"should": should, "minimum_should_match": 1}}))
+    }
+
+    fn compile_item(&self, item: &ExprGroupItem, span: Span) -> Result<Value, Error> {
+        match item {
+            ExprGroupItem::Expr(expr) => self.compile_expr(expr),
+            ExprGroupItem::ExprGroups(expr_groups) => self.compile_groups(expr_groups, span),
+            ExprGroupItem::Not(item) => Ok(must_not(self.compile_item(item, span)?)),
+        }
+    }
+
+    fn compile_expr(&self, expr: &Expr) -> Result<Value, Error> {
+        let (left, op, right) = match (&expr.left, &expr.right) {
+            // the field is expected to be on the left side
+            (left, Token::Identifier(_)) if !matches!(left, Token::Identifier(_)) => {
+                match flip(expr.op) {
+                    Some(op) => (&expr.right, op, left),
+                    None => return Err(unsupported(left, expr.span)),
+                }
+            }
+            (left, right) => (left, expr.op, right),
+        };
+
+        let field = self.field(left, expr.span)?;
+
+        match op {
+            SignOp::None => {
+                Err(Error::new(ErrorKind::Incomplete, Token::None, expr.span)
+                    .with_expected(&["sign"]))
+            }
+            SignOp::Eq | SignOp::AnyEq | SignOp::Neq => {
+                let value = value(right, expr.span)?;
+                let is_null = value.is_null();
+
+                let clause = if is_null {
+                    json!({"exists": {"field": field}})
+                } else {
+                    json!({"term": {field: value}})
+                };
+
+                // `= null` matches the documents without the field
+                if (op == SignOp::Neq) != is_null {
+                    return Ok(must_not(clause));
+                }
+
+                Ok(clause)
+            }
+            SignOp::Lt
+            | SignOp::Lte
+            | SignOp::Gt
+            | SignOp::Gte
+            | SignOp::AnyLt
+            | SignOp::AnyLte
+            | SignOp::AnyGt
+            | SignOp::AnyGte => {
+                let value = match value(right, expr.span)? {
+                    Value::Null => return Err(unsupported(right, expr.span)),
+                    value => value,
+                };
+                let range = match op {
+                    SignOp::Lt | SignOp::AnyLt => "lt",
+                    SignOp::Lte | SignOp::AnyLte => "lte",
+                    SignOp::Gt | SignOp::AnyGt => "gt",
+                    _ => "gte",
+                };
+
+                Ok(json!({"range": {field: {range: value}}}))
+            }
+            SignOp::Like | SignOp::AnyLike | SignOp::Nlike => {
+                let pattern = match right {
+                    Token::Text(value) | Token::Number(value) | Token::Bool(value) => {
+                        format!("*{}*", escape_wildcard(value))
+                    }
+                    _ => return Err(unsupported(right, expr.span)),
+                };
+                let clause =
+                    json!({"wildcard": {field: {"value": pattern, "case_insensitive": true}}});
+
+                if op == SignOp::Nlike {
+                    return Ok(must_not(clause));
+                }
+
+                Ok(clause)
+            }
+            SignOp::In | SignOp::Nin => {
+                let items = match right {
+                    Token::List(items) => items,
+                    _ => return Err(unsupported(right, expr.span).with_expected(&["list"])),
+                };
+
+                let mut values = Vec::with_capacity(items.len());
+                for item in items {
+                    match value(item, expr.span)? {
+                        Value::Null => return Err(unsupported(item, expr.span)),
+                        value => values.push(value),
+                    }
+                }
+
+                let clause = json!({"terms": {field: values}});
+
+                if op == SignOp::Nin {
+                    return Ok(must_not(clause));
+                }
+
+                Ok(clause)
+            }
+            SignOp::AnyNeq | SignOp::AnyNlike => Err(unsupported(right, expr.span)),
+        }
+    }
+
+    // field resolves the field name of an identifier token.
+    fn field(&self, token: &Token, span: Span) -> Result<String, Error> {
+        match token {
+            Token::Identifier(name) => (self.fields)(name)
+                .ok_or_else(|| Error::new(ErrorKind::UnknownIdentifier, token.clone(), span)),
+            _ => Err(unsupported(token, span).with_expected(&["identifier"])),
+        }
+    }
+}
+
+// flip returns the operator with swapped operands
+// (only for the operators that could be swapped).
+fn flip(op: SignOp) -> Option<SignOp> {
+    match op {
+        SignOp::Eq => Some(SignOp::Eq),
+        SignOp::Neq => Some(SignOp::Neq),
+        SignOp::Lt => Some(SignOp::Gt),
+        SignOp::Lte => Some(SignOp::Gte),
+        SignOp::Gt => Some(SignOp::Lt),
+        SignOp::Gte => Some(SignOp::Lte),
+        _ => None,
+    }
+}
+
+fn unsupported(token: &Token, span: Span) -> Error {
+    Error::new(ErrorKind::UnsupportedOperand, token.clone(), span)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{elasticsearch::compile, error::ErrorKind, parser::parse};
+
+    fn fields(name: &str) -> Option<String> {
+        match name {
+            "a" | "b" | "tags" => Some(name.to_string()),
+            "author" => Some("author.name.keyword".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_compile() {
+        struct Scenario {
+            input: &'static str,
+            expected: Value,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"a = 1",
+                expected: json!({"term": {"a": 1}}),
+            },
+            Scenario {
+                input: r"a = 'x' && b != -1.5 && author = true",
+                expected: json!({"bool": {"filter": [
+                    {"term": {"a": "x"}},
+                    {"bool": {"must_not": [{"term": {"b": -1.5}}]}},
+                    {"term": {"author.name.keyword": true}},
+                ]}}),
+            },
+            Scenario {
+                input: r"a = null || b != null",
+                expected: json!({"bool": {"should": [
+                    {"bool": {"must_not": [{"exists": {"field": "a"}}]}},
+                    {"exists": {"field": "b"}},
+                ], "minimum_should_match": 1}}),
+            },
+            Scenario {
+                input: r"a > 1 && a <= 10 || 2 < b && 'x' >= b",
+                expected: json!({"bool": {"should": [
+                    {"bool": {"filter": [
+                        {"range": {"a": {"gt": 1}}},
+                        {"range": {"a": {"lte": 10}}},
+                    ]}},
+                    {"bool": {"filter": [
+                        {"range": {"b": {"gt": 2}}},
+                        {"range": {"b": {"lte": "x"}}},
+                    ]}},
+                ], "minimum_should_match": 1}}),
+            },
+            Scenario {
+                input: r"a ~ 'x*y?z\w' && b !~ 1",
+                expected: json!({"bool": {"filter": [
+                    {"wildcard": {"a": {"value": r"*x\*y\?z\\w*", "case_insensitive": true}}},
+                    {"bool": {"must_not": [{"wildcard": {"b": {"value": "*1*", "case_insensitive": true}}}]}},
+                ]}}),
+            },
+            Scenario {
+                input: r"a in ['x', 1, true] && b !in []",
+                expected: json!({"bool": {"filter": [
+                    {"terms": {"a": ["x", 1, true]}},
+                    {"bool": {"must_not": [{"terms": {"b": []}}]}},
+                ]}}),
+            },
+            Scenario {
+                input: r"tags ?= 'x' && tags ?~ 'y' && tags ?>= 2",
+                expected: json!({"bool": {"filter": [
+                    {"term": {"tags": "x"}},
+                    {"wildcard": {"tags": {"value": "*y*", "case_insensitive": true}}},
+                    {"range": {"tags": {"gte": 2}}},
+                ]}}),
+            },
+            Scenario {
+                input: r"a = 1 && (b = 2 || !(a = 3 && b = 4)) || !(!(a = 5))",
+                expected: json!({"bool": {"should": [
+                    {"bool": {"filter": [
+                        {"term": {"a": 1}},
+                        {"bool": {"should": [
+                            {"term": {"b": 2}},
+                            {"bool": {"must_not": [{"bool": {"filter": [
+                                {"term": {"a": 3}},
+                                {"term": {"b": 4}},
+                            ]}}]}},
+                        ], "minimum_should_match": 1}},
+                    ]}},
+                    {"bool": {"must_not": [{"bool": {"must_not": [{"term": {"a": 5}}]}}]}},
+                ], "minimum_should_match": 1}}),
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let result = compile(&parse(scenario.input).unwrap(), fields).unwrap();
+
+            assert!(
+                result == scenario.expected,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        struct Scenario {
+            input: &'static str,
+            kind: ErrorKind,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"unknown = 1",
+                kind: ErrorKind::UnknownIdentifier,
+            },
+            Scenario {
+                input: r"a = b",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"1 = 2",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"'x' ~ a",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"a > null",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"a in [null]",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"tags ?!= 'x'",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"tags ?!~ 'x'",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            match compile(&parse(scenario.input).unwrap(), fields) {
+                Ok(result) => panic!("({}) Expected error, got {}", i, result),
+                Err(err) => assert!(
+                    err.kind == scenario.kind,
+                    "({}) Expected {:?}, got {:?}",
+                    i,
+                    scenario.kind,
+                    err.kind
+                ),
+            }
+        }
+    }
+}
@@ -1,21 +1,168 @@
-#[derive(Debug)]
-pub enum Error {
-    Buffer(String),
-    Unexpected(String),
-    Invalid(String),
-    Empty(String),
-    Incomplete(String),
+use crate::{scanner::Token, span::Span};
+
+// ErrorKind represents the type of a scanner, parser or compiler error.
+//
+// Each kind has a stable error code (see `ErrorKind::code`) that
+// could be used to branch on the error without matching its message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    InvalidUtf8,
+    UnexpectedChar,
+    InvalidIdentifier,
+    InvalidNumber,
+    InvalidText,
+    InvalidSign,
+    InvalidJoin,
+    InvalidGroup,
+    InvalidList,
+    InvalidPlaceholder,
+    InvalidComment,
+    UnexpectedToken,
+    Empty,
+    Incomplete,
+    UnknownIdentifier,
+    UnsupportedOperand,
+    NotAllowedSign,
+    TypeMismatch,
+    MissingParam,
+    TooManyClauses,
+    TooLong,
+    TooDeep,
+    TooManyExprs,
+    TextTooLong,
+    InvalidDatetime,
+}
+
+impl ErrorKind {
+    // code returns the stable machine readable code of the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidUtf8 => "invalid_utf8",
+            Self::UnexpectedChar => "unexpected_char",
+            Self::InvalidIdentifier => "invalid_identifier",
+            Self::InvalidNumber => "invalid_number",
+            Self::InvalidText => "invalid_text",
+            Self::InvalidSign => "invalid_sign",
+            Self::InvalidJoin => "invalid_join",
+            Self::InvalidGroup => "invalid_group",
+            Self::InvalidList => "invalid_list",
+            Self::InvalidPlaceholder => "invalid_placeholder",
+            Self::InvalidComment => "invalid_comment",
+            Self::UnexpectedToken => "unexpected_token",
+            Self::Empty => "empty",
+            Self::Incomplete => "incomplete",
+            Self::UnknownIdentifier => "unknown_identifier",
+            Self::UnsupportedOperand => "unsupported_operand",
+            Self::NotAllowedSign => "not_allowed_sign",
+            Self::TypeMismatch => "type_mismatch",
+            Self::MissingParam => "missing_param",
+            Self::TooManyClauses => "too_many_clauses",
+            Self::TooLong => "too_long",
+            Self::TooDeep => "too_deep",
+            Self::TooManyExprs => "too_many_exprs",
+            Self::TextTooLong => "text_too_long",
+            Self::InvalidDatetime => "invalid_datetime",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::InvalidUtf8 => "invalid UTF-8 sequence",
+            Self::UnexpectedChar => "unexpected character",
+            Self::InvalidIdentifier => "invalid identifier",
+            Self::InvalidNumber => "invalid number",
+            Self::InvalidText => "invalid quoted text",
+            Self::InvalidSign => "invalid sign operator",
+            Self::InvalidJoin => "invalid join operator",
+            Self::InvalidGroup => "invalid formatted group (missing closing bracket)",
+            Self::InvalidList => {
+                "invalid list (expected comma separated text, number, bool or null literals)"
+            }
+            Self::InvalidPlaceholder => "invalid placeholder (expected {:name} or ?)",
+            Self::InvalidComment => "invalid comment",
+            Self::UnexpectedToken => "unexpected token",
+            Self::Empty => "empty filter expression",
+            Self::Incomplete => "invalid or incomplete filter expression",
+            Self::UnknownIdentifier => "unknown or not allowed identifier",
+            Self::UnsupportedOperand => "unsupported operand for the sign operator",
+            Self::NotAllowedSign => "sign operator not allowed for the identifier",
+            Self::TypeMismatch => "operand type doesn't match the identifier type",
+            Self::MissingParam => "missing placeholder parameter",
+            Self::TooManyClauses => "normal form exceeds the max allowed clauses",
+            Self::TooLong => "filter expression exceeds the max allowed length",
+            Self::TooDeep => "group or negation exceeds the max allowed nesting depth",
+            Self::TooManyExprs => "filter expression exceeds the max allowed expressions",
+            Self::TextTooLong => "quoted text exceeds the max allowed length",
+            Self::InvalidDatetime => {
+                "invalid datetime text (expected YYYY-MM-DD[ HH:MM[:SS[.fff]][Z|+HH:MM]])"
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+// Error represents a structured scanner, parser or compiler error.
+//
+// It holds the offending token (if any), its location in the input and
+// the token kinds (see `Token::kind`) that were expected at that location.
+//
+// The token is always owned so that the error doesn't borrow from the input.
+#[derive(Debug, Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub token: Token<'static>,
+    pub span: Span,
+    pub expected: &'static [&'static str],
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, token: Token<'_>, span: Span) -> Self {
+        Self {
+            kind,
+            token: token.into_owned(),
+            span,
+            expected: &[],
+        }
+    }
+
+    // with_expected returns the error with the provided set of expected token kinds.
+    pub fn with_expected(mut self, expected: &'static [&'static str]) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    // code returns the stable machine readable code of the error.
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Error::Buffer(err) => write!(f, "Buffer: {err}"),
-            Error::Unexpected(err) => write!(f, "Unexpected: {err}"),
-            Error::Invalid(err) => write!(f, "Invalid: {err}"),
-            Error::Empty(err) => write!(f, "Empty: {err}"),
-            Error::Incomplete(err) => write!(f, "Incomplete: {err}"),
+        write!(f, "{}", self.kind.description())?;
+
+        if !matches!(self.token, Token::None | Token::Eof(_) | Token::List(_)) {
+            write!(f, " `{}` ({})", self.token.literal(), self.token.kind())?;
+        } else if matches!(self.token, Token::List(_)) {
+            write!(f, " ({})", self.token.kind())?;
         }
+
+        write!(f, " at {}", self.span.start)?;
+
+        if let Some((last, rest)) = self.expected.split_last() {
+            write!(f, ", expected ")?;
+            if !rest.is_empty() {
+                write!(f, "{} or ", rest.join(", "))?;
+            }
+            write!(f, "{last}")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -0,0 +1,480 @@
+use std::{borrow::Cow, cmp::Ordering};
+
+use serde_json::Value;
+
+use crate::{
+    error::{Error, ErrorKind},
+    json::value,
+    parser::{Expr, ExprGroupItem, ExprGroups},
+    scanner::{JoinOp, SignOp, Token},
+    span::Span,
+};
+
+// EvalError represents an evaluation error.
+//
+// Evaluating a parsed AST fails only for the parts that couldn't be
+// produced by the parser (eg. a manually constructed expression without
+// a sign operator or with a list operand for a non-membership operator).
+pub type EvalError = Error;
+
+// matches checks if the JSON record matches the provided filter.
+//
+// The identifiers are resolved as dot separated paths in the record
+// (eg. `a.b.0.c`, where the numeric segments are array indexes).
+// Missing paths are resolved as `null`.
+//
+// The sign operators are evaluated as follows:
+//   - `=`, `!=` - JSON equality (numbers are compared by their value)
+//   - `<`, `<=`, `>`, `>=` - numbers are compared by value, text
+//     lexicographically and bools with `false < true` (any other
+//     combination, including `null`, doesn't match)
+//   - `~`, `!~` - case insensitive contains of the text form of the
+//     operands (numbers and bools are converted to text, `null` never matches)
+//   - `in`, `!in` - equality with any of the list items
+//   - `?=`, `?!=`, `?~`, ... - at least one of the left operand array elements
+//     (or the left operand itself if it is not an array) matches the right operand
+//
+// The `&&` joined items bind tighter than the `||` joined ones and the
+// evaluation is short-circuited (aka. the errors of the skipped items are not reported).
+pub fn matches(expr_groups: &ExprGroups, record: &Value) -> Result<bool, EvalError> {
+    eval_groups(expr_groups, record, Span::default())
+}
+
+fn eval_groups(expr_groups: &ExprGroups, record: &Value, span: Span) -> Result<bool, EvalError> {
+    if expr_groups.is_empty() {
+        return Err(Error::new(ErrorKind::Empty, Token::None, span));
+    }
+
+    let mut result = false;
+    let mut conjunction = true;
+
+    for (i, expr_group) in expr_groups.iter().enumerate() {
+        if i > 0 && expr_group.join == JoinOp::Or {
+            result = result || conjunction;
+            conjunction = true;
+        }
+
+        if result || !conjunction {
+            continue;
+        }
+
+        conjunction = eval_item(&expr_group.item, record, expr_group.span)?;
+    }
+
+    Ok(result || conjunction)
+}
+
+fn eval_item(item: &ExprGroupItem, record: &Value, span: Span) -> Result<bool, EvalError> {
+    match item {
+        ExprGroupItem::Expr(expr) => eval_expr(expr, record),
+        ExprGroupItem::ExprGroups(expr_groups) => eval_groups(expr_groups, record, span),
+        ExprGroupItem::Not(item) => Ok(!eval_item(item, record, span)?),
+    }
+}
+
+fn eval_expr(expr: &Expr, record: &Value) -> Result<bool, EvalError> {
+    let left = operand(&expr.left, record, expr.span)?;
+
+    match expr.op {
+        SignOp::None => {
+            Err(Error::new(ErrorKind::Incomplete, Token::None, expr.span).with_expected(&["sign"]))
+        }
+        SignOp::In | SignOp::Nin => {
+            let items = match &expr.right {
+                Token::List(items) => items,
+                token => {
+                    return Err(
+                        Error::new(ErrorKind::UnsupportedOperand, token.clone(), expr.span)
+                            .with_expected(&["list"]),
+                    )
+                }
+            };
+
+            let mut found = false;
+            for item in items {
+                if equals(&left, &value(item, expr.span)?) {
+                    found = true;
+                    break;
+                }
+            }
+
+            Ok(found == (expr.op == SignOp::In))
+        }
+        SignOp::AnyEq
+        | SignOp::AnyNeq
+        | SignOp::AnyLike
+        | SignOp::AnyNlike
+        | SignOp::AnyLt
+        | SignOp::AnyLte
+        | SignOp::AnyGt
+        | SignOp::AnyGte => {
+            let right = operand(&expr.right, record, expr.span)?;
+            let op = scalar(expr.op);
+
+            Ok(match left.as_ref() {
+                Value::Array(elements) => elements
+                    .iter()
+                    .any(|element| compare_with(op, element, &right)),
+                left => compare_with(op, left, &right),
+            })
+        }
+        op => {
+            let right = operand(&expr.right, record, expr.span)?;
+
+            Ok(compare_with(op, &left, &right))
+        }
+    }
+}
+
+// operand resolves the token value (identifiers are resolved from the record).
+fn operand<'v>(token: &Token, record: &'v Value, span: Span) -> Result<Cow<'v, Value>, EvalError> {
+    match token {
+        Token::Identifier(path) => {
+            Ok(resolve(record, path).map_or(Cow::Owned(Value::Null), Cow::Borrowed))
+        }
+        token => value(token, span).map(Cow::Owned),
+    }
+}
+
+// resolve returns the value at the dot separated path.
+fn resolve<'v>(record: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.')
+        .try_fold(record, |current, segment| match current {
+            Value::Object(object) => object.get(segment),
+            Value::Array(array) => segment.parse::<usize>().ok().and_then(|i| array.get(i)),
+            _ => None,
+        })
+}
+
+// scalar returns the non-array variant of an `Any*` operator.
+fn scalar(op: SignOp) -> SignOp {
+    match op {
+        SignOp::AnyEq => SignOp::Eq,
+        SignOp::AnyNeq => SignOp::Neq,
+        SignOp::AnyLike => SignOp::Like,
+        SignOp::AnyNlike => SignOp::Nlike,
+        SignOp::AnyLt => SignOp::Lt,
+        SignOp::AnyLte => SignOp::Lte,
+        SignOp::AnyGt => SignOp::Gt,
+        SignOp::AnyGte => SignOp::Gte,
+        op => op,
+    }
+}
+
+// compare_with checks if the values satisfy the (non-array) sign operator.
+fn compare_with(op: SignOp, left: &Value, right: &Value) -> bool {
+    match op {
+        SignOp::Eq => equals(left, right),
+        SignOp::Neq => !equals(left, right),
+        SignOp::Like => like(left, right),
+        SignOp::Nlike => !like(left, right),
+        SignOp::Lt => compare(left, right) == Some(Ordering::Less),
+        SignOp::Lte => matches!(compare(left, right), Some(Ordering::Less | Ordering::Equal)),
+        SignOp::Gt => compare(left, right) == Some(Ordering::Greater),
+        SignOp::Gte => matches!(
+            compare(left, right),
+            Some(Ordering::Greater | Ordering::Equal)
+        ),
+        _ => false,
+    }
+}
+
+// equals checks if the values are equal (numbers are compared by value).
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64() == right.as_f64(),
+        (left, right) => left == right,
+    }
+}
+
+// compare returns the ordering of two values of the same comparable type.
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64()?.partial_cmp(&right.as_f64()?),
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        (Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+// like checks if the text form of the left value contains the right one (case insensitive).
+fn like(left: &Value, right: &Value) -> bool {
+    match (text(left), text(right)) {
+        (Some(left), Some(right)) => left.to_lowercase().contains(&right.to_lowercase()),
+        _ => false,
+    }
+}
+
+// text returns the text form of a scalar value.
+fn text(value: &Value) -> Option<Cow<'_, str>> {
+    match value {
+        Value::String(value) => Some(Cow::Borrowed(value)),
+        Value::Number(value) => Some(Cow::Owned(value.to_string())),
+        Value::Bool(value) => Some(Cow::Owned(value.to_string())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        error::ErrorKind,
+        eval::matches,
+        parser::{parse, Expr, ExprGroup, ExprGroups},
+        scanner::{JoinOp, SignOp, Token},
+    };
+
+    #[test]
+    fn test_matches() {
+        struct Scenario {
+            input: &'static str,
+            expected: bool,
+        }
+
+        let record = json!({
+            "id": 10,
+            "price": 2.5,
+            "title": "Hello World",
+            "active": true,
+            "deleted": null,
+            "author": {"name": "Zoë", "roles": ["admin", "Editor"]},
+            "scores": [1, 5, 10],
+            "items": [{"sku": "a1"}, {"sku": "b2"}],
+        });
+
+        let scenarios = [
+            // equality
+            Scenario {
+                input: r"id = 10",
+                expected: true,
+            },
+            Scenario {
+                input: r"id = 10.0",
+                expected: true,
+            },
+            Scenario {
+                input: r"id = '10'",
+                expected: false,
+            },
+            Scenario {
+                input: r"id != 11",
+                expected: true,
+            },
+            Scenario {
+                input: r"title = 'Hello World'",
+                expected: true,
+            },
+            Scenario {
+                input: r"active = true && active != false",
+                expected: true,
+            },
+            Scenario {
+                input: r"deleted = null && missing = null && missing.nested = null",
+                expected: true,
+            },
+            Scenario {
+                input: r"title != null",
+                expected: true,
+            },
+            Scenario {
+                input: r"id = price",
+                expected: false,
+            },
+            Scenario {
+                input: r"author.name = 'Zoë'",
+                expected: true,
+            },
+            Scenario {
+                input: r"items.1.sku = 'b2' && scores.0 = 1",
+                expected: true,
+            },
+            // ordering
+            Scenario {
+                input: r"id > 9 && id >= 10 && id < 11 && id <= 10",
+                expected: true,
+            },
+            Scenario {
+                input: r"price < id && 3 > price",
+                expected: true,
+            },
+            Scenario {
+                input: r"title > 'Apple' && title < 'World'",
+                expected: true,
+            },
+            Scenario {
+                input: r"id > '9'",
+                expected: false,
+            },
+            Scenario {
+                input: r"deleted < 1 || deleted >= 1",
+                expected: false,
+            },
+            Scenario {
+                input: r"active > false",
+                expected: true,
+            },
+            // like
+            Scenario {
+                input: r"title ~ 'o wor'",
+                expected: true,
+            },
+            Scenario {
+                input: r"title ~ 'xyz'",
+                expected: false,
+            },
+            Scenario {
+                input: r"title !~ 'xyz'",
+                expected: true,
+            },
+            Scenario {
+                input: r"id ~ 1 && price ~ '.5'",
+                expected: true,
+            },
+            Scenario {
+                input: r"author.name ~ 'ZOË'",
+                expected: true,
+            },
+            Scenario {
+                input: r"deleted ~ ''",
+                expected: false,
+            },
+            // membership
+            Scenario {
+                input: r"id in [1, 10.0, 'x']",
+                expected: true,
+            },
+            Scenario {
+                input: r"id in []",
+                expected: false,
+            },
+            Scenario {
+                input: r"id !in [1, 2]",
+                expected: true,
+            },
+            Scenario {
+                input: r"deleted in [null]",
+                expected: true,
+            },
+            // any
+            Scenario {
+                input: r"author.roles ?= 'admin'",
+                expected: true,
+            },
+            Scenario {
+                input: r"author.roles ?= 'guest'",
+                expected: false,
+            },
+            Scenario {
+                input: r"author.roles ?!= 'admin'",
+                expected: true,
+            },
+            Scenario {
+                input: r"author.roles ?~ 'edit'",
+                expected: true,
+            },
+            Scenario {
+                input: r"author.roles ?!~ 'i'",
+                expected: false,
+            },
+            Scenario {
+                input: r"scores ?> 9 && scores ?< 2 && scores ?>= 10 && scores ?<= 1",
+                expected: true,
+            },
+            Scenario {
+                input: r"scores ?> 10",
+                expected: false,
+            },
+            Scenario {
+                input: r"id ?= 10",
+                expected: true,
+            },
+            Scenario {
+                input: r"missing ?= null",
+                expected: true,
+            },
+            // precedence and groups
+            Scenario {
+                input: r"id = 1 || id = 10 && active = true",
+                expected: true,
+            },
+            Scenario {
+                input: r"id = 10 || id = 1 && active = false",
+                expected: true,
+            },
+            Scenario {
+                input: r"(id = 10 || id = 1) && active = false",
+                expected: false,
+            },
+            Scenario {
+                input: r"id = 1 && active = true || id = 2",
+                expected: false,
+            },
+            Scenario {
+                input: r"!(id = 1) && !!active = true",
+                expected: true,
+            },
+            Scenario {
+                input: r"!(id = 10 && (title ~ 'hello' || id = 1))",
+                expected: false,
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let result = matches(&parse(scenario.input).unwrap(), &record).unwrap();
+
+            assert!(
+                result == scenario.expected,
+                "({}) Expected {} for {}, got {}",
+                i,
+                scenario.expected,
+                scenario.input,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_matches_errors() {
+        fn expr(op: SignOp, right: Token<'static>) -> ExprGroups<'static> {
+            ExprGroups::from(vec![ExprGroup::new(
+                JoinOp::And,
+                Expr::new(Token::Identifier("a".into()), op, right),
+            )])
+        }
+
+        let scenarios = [
+            (ExprGroups::new(), ErrorKind::Empty),
+            (
+                expr(SignOp::None, Token::Number("1".into())),
+                ErrorKind::Incomplete,
+            ),
+            (
+                expr(SignOp::In, Token::Number("1".into())),
+                ErrorKind::UnsupportedOperand,
+            ),
+            (
+                expr(SignOp::Eq, Token::List(vec![])),
+                ErrorKind::UnsupportedOperand,
+            ),
+            (
+                expr(SignOp::Eq, Token::Number("x".into())),
+                ErrorKind::InvalidNumber,
+            ),
+        ];
+
+        for (i, (ast, kind)) in scenarios.iter().enumerate() {
+            match matches(ast, &json!({"a": 1})) {
+                Ok(result) => panic!("({}) Expected error, got {}", i, result),
+                Err(err) => assert!(
+                    err.kind == *kind,
+                    "({}) Expected {:?}, got {:?}",
+                    i,
+                    kind,
+                    err.kind
+                ),
+            }
+        }
+    }
+}
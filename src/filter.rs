@@ -0,0 +1,1155 @@
+use std::{borrow::Cow, cmp::Ordering};
+
+use crate::{
+    error::{Error, ErrorKind},
+    parser::{Expr, ExprGroupItem, ExprGroups},
+    scanner::{JoinOp, SignOp, Token},
+    schema::{check_types, FieldType},
+    span::Span,
+};
+
+// Value represents a single record field value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'a> {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(Cow<'a, str>),
+    // Composite is a non-null array or struct value
+    // (it is not equal, ordered or like matched with any other value).
+    Composite,
+}
+
+impl From<bool> for Value<'_> {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<i64> for Value<'_> {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<f64> for Value<'_> {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl<'a> From<&'a str> for Value<'a> {
+    fn from(value: &'a str) -> Self {
+        Value::Text(Cow::Borrowed(value))
+    }
+}
+
+impl From<String> for Value<'_> {
+    fn from(value: String) -> Self {
+        Value::Text(Cow::Owned(value))
+    }
+}
+
+impl<'a, T: Into<Value<'a>>> From<Option<T>> for Value<'a> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+// Field represents a pre-resolved filter identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Field {
+    name: String,
+    segments: Vec<String>,
+}
+
+impl Field {
+    fn new(name: &str) -> Self {
+        Field {
+            name: name.to_string(),
+            segments: name.split('.').map(str::to_string).collect(),
+        }
+    }
+
+    // name returns the full identifier name (eg. `author.name`).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // segments returns the dot separated parts of the identifier (eg. `["author", "name"]`).
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+}
+
+// Record represents a single filtered item (eg. a row or a log entry).
+pub trait Record {
+    // get returns the value of the field or `None` if the record doesn't have it
+    // (missing fields are evaluated as `null`).
+    fn get(&self, field: &Field) -> Option<Value<'_>>;
+
+    // any checks if at least one of the array field elements satisfies
+    // the predicate (used by the `?=`, `?~`, etc. operators).
+    //
+    // The default implementation treats the field as a single element array.
+    fn any(&self, field: &Field, predicate: &mut dyn FnMut(&Value) -> bool) -> bool {
+        predicate(&self.get(field).unwrap_or(Value::Null))
+    }
+}
+
+// Filterable represents a value that could be resolved by a field path
+// (usually implemented with `#[derive(Filterable)]` when the `derive` feature is enabled).
+pub trait Filterable {
+    // filter_get returns the value at the path relative to self
+    // (an empty path refers to self) or `None` if there is no such value.
+    fn filter_get(&self, path: &[String]) -> Option<Value<'_>>;
+
+    // filter_any checks if at least one of the elements at the path
+    // satisfies the predicate.
+    //
+    // The default implementation treats the value as a single element array.
+    fn filter_any(&self, path: &[String], predicate: &mut dyn FnMut(&Value) -> bool) -> bool {
+        predicate(&self.filter_get(path).unwrap_or(Value::Null))
+    }
+
+    // filter_type returns the type of the values at the path relative to the type
+    // or `None` if it is unknown (used by [`compile_for`] to type check the filter).
+    //
+    // The default implementation doesn't resolve any type.
+    fn filter_type(_path: &[String]) -> Option<FieldType> {
+        None
+    }
+}
+
+macro_rules! impl_filterable {
+    ($($ty:ty: $kind:ident => |$value:ident| $expr:expr),* $(,)?) => {
+        $(
+            impl Filterable for $ty {
+                fn filter_get(&self, path: &[String]) -> Option<Value<'_>> {
+                    let $value = self;
+                    path.is_empty().then(|| $expr)
+                }
+
+                fn filter_type(path: &[String]) -> Option<FieldType> {
+                    path.is_empty().then_some(FieldType::$kind)
+                }
+            }
+        )*
+    };
+}
+
+impl_filterable! {
+    bool: Bool => |value| Value::Bool(*value),
+    i8: Number => |value| Value::Int(i64::from(*value)),
+    i16: Number => |value| Value::Int(i64::from(*value)),
+    i32: Number => |value| Value::Int(i64::from(*value)),
+    i64: Number => |value| Value::Int(*value),
+    isize: Number => |value| Value::Int(*value as i64),
+    u8: Number => |value| Value::Int(i64::from(*value)),
+    u16: Number => |value| Value::Int(i64::from(*value)),
+    u32: Number => |value| Value::Int(i64::from(*value)),
+    u64: Number => |value| i64::try_from(*value).map_or(Value::Float(*value as f64), Value::Int),
+    usize: Number => |value| i64::try_from(*value).map_or(Value::Float(*value as f64), Value::Int),
+    f32: Number => |value| Value::Float(f64::from(*value)),
+    f64: Number => |value| Value::Float(*value),
+    str: String => |value| Value::Text(Cow::Borrowed(value)),
+    String: String => |value| Value::Text(Cow::Borrowed(value)),
+    Cow<'_, str>: String => |value| Value::Text(Cow::Borrowed(value)),
+}
+
+impl<T: Filterable + ?Sized> Filterable for &T {
+    fn filter_get(&self, path: &[String]) -> Option<Value<'_>> {
+        (**self).filter_get(path)
+    }
+
+    fn filter_any(&self, path: &[String], predicate: &mut dyn FnMut(&Value) -> bool) -> bool {
+        (**self).filter_any(path, predicate)
+    }
+
+    fn filter_type(path: &[String]) -> Option<FieldType> {
+        T::filter_type(path)
+    }
+}
+
+impl<T: Filterable + ?Sized> Filterable for Box<T> {
+    fn filter_get(&self, path: &[String]) -> Option<Value<'_>> {
+        (**self).filter_get(path)
+    }
+
+    fn filter_any(&self, path: &[String], predicate: &mut dyn FnMut(&Value) -> bool) -> bool {
+        (**self).filter_any(path, predicate)
+    }
+
+    fn filter_type(path: &[String]) -> Option<FieldType> {
+        T::filter_type(path)
+    }
+}
+
+// Option values are resolved as `null` when missing.
+impl<T: Filterable> Filterable for Option<T> {
+    fn filter_get(&self, path: &[String]) -> Option<Value<'_>> {
+        match self {
+            Some(value) => value.filter_get(path),
+            None => Some(Value::Null),
+        }
+    }
+
+    fn filter_any(&self, path: &[String], predicate: &mut dyn FnMut(&Value) -> bool) -> bool {
+        match self {
+            Some(value) => value.filter_any(path, predicate),
+            None => predicate(&Value::Null),
+        }
+    }
+
+    fn filter_type(path: &[String]) -> Option<FieldType> {
+        T::filter_type(path)
+    }
+}
+
+// Slices are not comparable as a whole (they are only not `null`) and are used
+// by the `?=`, `?~`, etc. operators (the path is resolved for each element, eg. `items.id ?= 1`).
+impl<T: Filterable> Filterable for [T] {
+    fn filter_get(&self, _path: &[String]) -> Option<Value<'_>> {
+        Some(Value::Composite)
+    }
+
+    fn filter_any(&self, path: &[String], predicate: &mut dyn FnMut(&Value) -> bool) -> bool {
+        self.iter().any(|item| item.filter_any(path, predicate))
+    }
+
+    fn filter_type(path: &[String]) -> Option<FieldType> {
+        T::filter_type(path).map(FieldType::array)
+    }
+}
+
+impl<T: Filterable> Filterable for Vec<T> {
+    fn filter_get(&self, path: &[String]) -> Option<Value<'_>> {
+        self.as_slice().filter_get(path)
+    }
+
+    fn filter_any(&self, path: &[String], predicate: &mut dyn FnMut(&Value) -> bool) -> bool {
+        self.as_slice().filter_any(path, predicate)
+    }
+
+    fn filter_type(path: &[String]) -> Option<FieldType> {
+        <[T]>::filter_type(path)
+    }
+}
+
+#[cfg(feature = "derive")]
+pub use fexpr_derive::Filterable;
+
+// CompiledFilter is a reusable filter predicate.
+//
+// The identifiers, number literals and like patterns are resolved once
+// during the compilation, so matching doesn't allocate (the `~` operands
+// are lowercased char by char and the numbers are formatted on the stack).
+#[derive(Debug, Clone)]
+pub struct CompiledFilter {
+    node: Node,
+}
+
+impl CompiledFilter {
+    // matches checks if the record satisfies the filter.
+    pub fn matches(&self, record: &impl Record) -> bool {
+        self.node.matches(record)
+    }
+}
+
+// compile compiles the provided AST into a reusable filter.
+//
+// The sign operators follow the same semantics as the `eval` feature:
+//   - `=`, `!=` - numbers are compared by their value, all other values
+//     are equal only to the values of the same type
+//   - `<`, `<=`, `>`, `>=` - numbers are compared by value, text
+//     lexicographically and bools with `false < true` (any other
+//     combination, including `null`, doesn't match)
+//   - `~`, `!~` - case insensitive contains of the text form of the
+//     operands (`null` never matches)
+//   - `in`, `!in` - equality with any of the list items
+//   - `?=`, `?!=`, `?~`, ... - at least one of the left operand elements
+//     matches the right operand (see [`Record::any`])
+//
+// The `&&` joined items bind tighter than the `||` joined ones.
+pub fn compile(expr_groups: &ExprGroups) -> Result<CompiledFilter, Error> {
+    Ok(CompiledFilter {
+        node: compile_groups(expr_groups, Span::default(), &|_| None)?,
+    })
+}
+
+// compile_for compiles the provided AST into a reusable filter of the `T` records
+// (see [`compile`]) and checks the operands against the identifier types
+// resolved by [`Filterable::filter_type`].
+//
+// A literal or identifier that doesn't match the identifier type (eg. `count = 'x'`
+// for an integer field) is reported as `TypeMismatch` error. The identifiers
+// without a type are not checked (and the missing ones are still evaluated as `null`).
+pub fn compile_for<T: Filterable + ?Sized>(
+    expr_groups: &ExprGroups,
+) -> Result<CompiledFilter, Error> {
+    let field_type = |name: &str| T::filter_type(Field::new(name).segments());
+
+    Ok(CompiledFilter {
+        node: compile_groups(expr_groups, Span::default(), &field_type)?,
+    })
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+    Condition(Condition),
+}
+
+impl Node {
+    fn matches<R: Record>(&self, record: &R) -> bool {
+        match self {
+            Node::And(nodes) => nodes.iter().all(|node| node.matches(record)),
+            Node::Or(nodes) => nodes.iter().any(|node| node.matches(record)),
+            Node::Not(node) => !node.matches(record),
+            Node::Condition(condition) => condition.matches(record),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Condition {
+    left: Operand,
+    any: bool,
+    matcher: Matcher,
+}
+
+impl Condition {
+    fn matches<R: Record>(&self, record: &R) -> bool {
+        match &self.left {
+            Operand::Field(field) if self.any => {
+                record.any(field, &mut |left| self.matcher.matches(left, record))
+            }
+            left => left.with(record, |left| self.matcher.matches(left, record)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Field(Field),
+    Literal(Value<'static>),
+}
+
+impl Operand {
+    // with calls f with the resolved operand value.
+    fn with<R: Record, T>(&self, record: &R, f: impl FnOnce(&Value) -> T) -> T {
+        match self {
+            Operand::Field(field) => f(&record.get(field).unwrap_or(Value::Null)),
+            Operand::Literal(value) => f(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    // Compare is one of the `=`, `!=`, `<`, `<=`, `>`, `>=` comparisons.
+    Compare(SignOp, Operand),
+    Like(Pattern, bool),
+    In(Vec<Value<'static>>, bool),
+}
+
+impl Matcher {
+    fn matches<R: Record>(&self, left: &Value, record: &R) -> bool {
+        match self {
+            Matcher::Compare(op, right) => right.with(record, |right| match op {
+                SignOp::Eq => equals(left, right),
+                SignOp::Neq => !equals(left, right),
+                SignOp::Lt => compare(left, right) == Some(Ordering::Less),
+                SignOp::Lte => {
+                    matches!(compare(left, right), Some(Ordering::Less | Ordering::Equal))
+                }
+                SignOp::Gt => compare(left, right) == Some(Ordering::Greater),
+                SignOp::Gte => matches!(
+                    compare(left, right),
+                    Some(Ordering::Greater | Ordering::Equal)
+                ),
+                _ => false,
+            }),
+            Matcher::Like(pattern, negated) => pattern.matches(left, record) != *negated,
+            Matcher::In(items, negated) => items.iter().any(|item| equals(left, item)) != *negated,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    // Text is the pre-lowercased (char by char) text form of a literal.
+    Text(String),
+    // Field is a pattern resolved from the record on every match.
+    Field(Field),
+    // Never is the pattern of a `null` literal.
+    Never,
+}
+
+impl Pattern {
+    fn matches<R: Record>(&self, left: &Value, record: &R) -> bool {
+        let matches = with_text(left, |left| match self {
+            Pattern::Text(needle) => {
+                // a lowercase non-ASCII needle can't be a part of a lowercased ASCII text
+                if left.is_ascii() && !needle.is_ascii() {
+                    return false;
+                }
+                contains_lowercase(left, needle.chars())
+            }
+            Pattern::Field(field) => match record.get(field) {
+                Some(right) => with_text(&right, |right| {
+                    contains_lowercase(left, right.chars().flat_map(char::to_lowercase))
+                })
+                .unwrap_or_default(),
+                None => false,
+            },
+            Pattern::Never => false,
+        });
+
+        matches.unwrap_or_default()
+    }
+}
+
+// contains_lowercase checks if the lowercase form of the haystack contains
+// the lowercase needle chars.
+//
+// The haystack is lowercased char by char (with `char::to_lowercase`) while
+// comparing, without allocating its lowercase form first.
+fn contains_lowercase<N>(haystack: &str, needle: N) -> bool
+where
+    N: Iterator<Item = char> + Clone,
+{
+    let mut haystack = haystack.chars().flat_map(char::to_lowercase);
+
+    loop {
+        let mut candidate = haystack.clone();
+        if needle.clone().all(|ch| candidate.next() == Some(ch)) {
+            return true;
+        }
+
+        if haystack.next().is_none() {
+            return false;
+        }
+    }
+}
+
+fn compile_groups(
+    expr_groups: &ExprGroups,
+    span: Span,
+    field_type: &dyn Fn(&str) -> Option<FieldType>,
+) -> Result<Node, Error> {
+    if expr_groups.is_empty() {
+        return Err(Error::new(ErrorKind::Empty, Token::None, span));
+    }
+
+    // `&&` binds tighter than `||`
+    let mut conjunctions: Vec<Vec<Node>> = Vec::new();
+    for (i, expr_group) in expr_groups.iter().enumerate() {
+        let node = compile_item(&expr_group.item, expr_group.span, field_type)?;
+
+        match conjunctions.last_mut() {
+            Some(conjunction) if i == 0 || expr_group.join == JoinOp::And => conjunction.push(node),
+            _ => conjunctions.push(vec![node]),
+        }
+    }
+
+    let disjunction = conjunctions
+        .into_iter()
+        .map(|nodes| combine(nodes, Node::And))
+        .collect();
+
+    Ok(combine(disjunction, Node::Or))
+}
+
+// combine combines the nodes with the logical operator
+// (a single node is returned as it is).
+fn combine(mut nodes: Vec<Node>, op: fn(Vec<Node>) -> Node) -> Node {
+    if nodes.len() == 1 {
+        return nodes.remove(0);
+    }
+
+    op(nodes)
+}
+
+fn compile_item(
+    item: &ExprGroupItem,
+    span: Span,
+    field_type: &dyn Fn(&str) -> Option<FieldType>,
+) -> Result<Node, Error> {
+    match item {
+        ExprGroupItem::Expr(expr) => Ok(Node::Condition(compile_expr(expr, field_type)?)),
+        ExprGroupItem::ExprGroups(expr_groups) => compile_groups(expr_groups, span, field_type),
+        ExprGroupItem::Not(item) => Ok(Node::Not(Box::new(compile_item(item, span, field_type)?))),
+    }
+}
+
+fn compile_expr(
+    expr: &Expr,
+    field_type: &dyn Fn(&str) -> Option<FieldType>,
+) -> Result<Condition, Error> {
+    let left = operand(&expr.left, expr.span)?;
+
+    let (op, any) = match expr.op {
+        SignOp::None => {
+            return Err(
+                Error::new(ErrorKind::Incomplete, Token::None, expr.span).with_expected(&["sign"])
+            )
+        }
+        SignOp::AnyEq => (SignOp::Eq, true),
+        SignOp::AnyNeq => (SignOp::Neq, true),
+        SignOp::AnyLike => (SignOp::Like, true),
+        SignOp::AnyNlike => (SignOp::Nlike, true),
+        SignOp::AnyLt => (SignOp::Lt, true),
+        SignOp::AnyLte => (SignOp::Lte, true),
+        SignOp::AnyGt => (SignOp::Gt, true),
+        SignOp::AnyGte => (SignOp::Gte, true),
+        op => (op, false),
+    };
+
+    check_types(expr, field_type)?;
+
+    let matcher = match op {
+        SignOp::Like | SignOp::Nlike => {
+            Matcher::Like(pattern(&expr.right, expr.span)?, op == SignOp::Nlike)
+        }
+        SignOp::In | SignOp::Nin => {
+            let items = match &expr.right {
+                Token::List(items) => items,
+                token => {
+                    return Err(
+                        Error::new(ErrorKind::UnsupportedOperand, token.clone(), expr.span)
+                            .with_expected(&["list"]),
+                    )
+                }
+            };
+            let values = items
+                .iter()
+                .map(|item| literal(item, expr.span))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Matcher::In(values, op == SignOp::Nin)
+        }
+        op => Matcher::Compare(op, operand(&expr.right, expr.span)?),
+    };
+
+    Ok(Condition { left, any, matcher })
+}
+
+fn operand(token: &Token, span: Span) -> Result<Operand, Error> {
+    match token {
+        Token::Identifier(name) => Ok(Operand::Field(Field::new(name))),
+        token => Ok(Operand::Literal(literal(token, span)?)),
+    }
+}
+
+// pattern pre-builds the like matcher of the right operand.
+fn pattern(token: &Token, span: Span) -> Result<Pattern, Error> {
+    match token {
+        Token::Identifier(name) => Ok(Pattern::Field(Field::new(name))),
+        Token::Null(_) => Ok(Pattern::Never),
+        Token::Text(value) | Token::Number(value) | Token::Bool(value) => Ok(Pattern::Text(
+            value.chars().flat_map(char::to_lowercase).collect(),
+        )),
+        _ => Err(Error::new(
+            ErrorKind::UnsupportedOperand,
+            token.clone(),
+            span,
+        )),
+    }
+}
+
+// literal converts a literal token into a value.
+fn literal(token: &Token, span: Span) -> Result<Value<'static>, Error> {
+    match token {
+        Token::Null(_) => Ok(Value::Null),
+        Token::Bool(value) => Ok(Value::Bool(value == "true")),
+        Token::Text(value) => Ok(Value::Text(Cow::Owned(value.to_string()))),
+        Token::Number(value) => match value.parse::<i64>() {
+            Ok(number) => Ok(Value::Int(number)),
+            Err(_) => value
+                .parse::<f64>()
+                .map(Value::Float)
+                .map_err(|_| Error::new(ErrorKind::InvalidNumber, token.clone(), span)),
+        },
+        _ => Err(Error::new(
+            ErrorKind::UnsupportedOperand,
+            token.clone(),
+            span,
+        )),
+    }
+}
+
+// number returns the numeric value as float.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(value) => Some(*value as f64),
+        Value::Float(value) => Some(*value),
+        _ => None,
+    }
+}
+
+// equals checks if the values are equal (numbers are compared by value).
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Composite, _) | (_, Value::Composite) => false,
+        _ => match (number(left), number(right)) {
+            (Some(left), Some(right)) => left == right,
+            _ => left == right,
+        },
+    }
+}
+
+// compare returns the ordering of two values of the same comparable type.
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Text(left), Value::Text(right)) => Some(left.cmp(right)),
+        (Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+        (left, right) => number(left)?.partial_cmp(&number(right)?),
+    }
+}
+
+// with_text calls f with the text form of a non-null scalar value
+// (the numbers are formatted into a stack buffer).
+fn with_text<T>(value: &Value, f: impl FnOnce(&str) -> T) -> Option<T> {
+    let mut buf = TextBuf::default();
+
+    let text = match value {
+        Value::Null | Value::Composite => return None,
+        Value::Bool(true) => "true",
+        Value::Bool(false) => "false",
+        Value::Int(value) => buf.format(value),
+        Value::Float(value) => buf.format(value),
+        Value::Text(value) => value,
+    };
+
+    Some(f(text))
+}
+
+// TextBuf is a fixed size stack buffer for the text form of the numbers
+// (the longest f64 text form, eg. `-0.000...5e-324`, has less than 350 chars).
+struct TextBuf {
+    bytes: [u8; 512],
+    len: usize,
+}
+
+impl Default for TextBuf {
+    fn default() -> Self {
+        TextBuf {
+            bytes: [0; 512],
+            len: 0,
+        }
+    }
+}
+
+impl TextBuf {
+    // format writes the value into the buffer and returns the written text.
+    fn format(&mut self, value: &impl std::fmt::Display) -> &str {
+        use std::fmt::Write;
+
+        self.len = 0;
+        // the buffer is large enough for any number
+        let _ = write!(self, "{value}");
+
+        std::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl std::fmt::Write for TextBuf {
+    fn write_str(&mut self, text: &str) -> std::fmt::Result {
+        let end = self.len + text.len();
+        if end > self.bytes.len() {
+            return Err(std::fmt::Error);
+        }
+
+        self.bytes[self.len..end].copy_from_slice(text.as_bytes());
+        self.len = end;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::ErrorKind,
+        filter::{compile, Field, Record, Value},
+        parser::{parse, Expr, ExprGroup, ExprGroups},
+        scanner::{JoinOp, SignOp, Token},
+    };
+
+    struct Entry {
+        level: &'static str,
+        status: i64,
+        duration: f64,
+        user: Option<&'static str>,
+        tags: Vec<&'static str>,
+    }
+
+    impl Record for Entry {
+        fn get(&self, field: &Field) -> Option<Value<'_>> {
+            match field.segments() {
+                [name] if name == "level" => Some(self.level.into()),
+                [name] if name == "status" => Some(self.status.into()),
+                [name] if name == "duration" => Some(self.duration.into()),
+                [a, b] if a == "user" && b == "name" => Some(self.user.into()),
+                _ => None,
+            }
+        }
+
+        fn any(&self, field: &Field, predicate: &mut dyn FnMut(&Value) -> bool) -> bool {
+            match field.name() {
+                "tags" => self.tags.iter().any(|tag| predicate(&Value::from(*tag))),
+                _ => predicate(&self.get(field).unwrap_or(Value::Null)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_compiled_filter_matches() {
+        struct Scenario {
+            input: &'static str,
+            expected: bool,
+        }
+
+        let entry = Entry {
+            level: "Error",
+            status: 500,
+            duration: 1.5,
+            user: None,
+            tags: vec!["db", "Timeout"],
+        };
+
+        let scenarios = [
+            // equality
+            Scenario {
+                input: r"level = 'Error'",
+                expected: true,
+            },
+            Scenario {
+                input: r"level = 'error'",
+                expected: false,
+            },
+            Scenario {
+                input: r"status = 500.0 && status != 404",
+                expected: true,
+            },
+            Scenario {
+                input: r"status = '500'",
+                expected: false,
+            },
+            Scenario {
+                input: r"user.name = null && missing = null",
+                expected: true,
+            },
+            Scenario {
+                input: r"level != null",
+                expected: true,
+            },
+            Scenario {
+                input: r"status = duration",
+                expected: false,
+            },
+            // ordering
+            Scenario {
+                input: r"status > 499 && status >= 500 && status < 501 && status <= 500",
+                expected: true,
+            },
+            Scenario {
+                input: r"duration < status && 2 > duration",
+                expected: true,
+            },
+            Scenario {
+                input: r"level > 'Debug' && level < 'Warn'",
+                expected: true,
+            },
+            Scenario {
+                input: r"status > '1'",
+                expected: false,
+            },
+            Scenario {
+                input: r"user.name < 1 || user.name >= 1",
+                expected: false,
+            },
+            // like
+            Scenario {
+                input: r"level ~ 'ERR'",
+                expected: true,
+            },
+            Scenario {
+                input: r"level ~ 'e.r'",
+                expected: false,
+            },
+            Scenario {
+                input: r"level !~ 'warn'",
+                expected: true,
+            },
+            Scenario {
+                input: r"status ~ 50 && duration ~ '.5'",
+                expected: true,
+            },
+            Scenario {
+                input: r"level ~ null || user.name ~ ''",
+                expected: false,
+            },
+            Scenario {
+                input: r"'server error' ~ level",
+                expected: true,
+            },
+            // membership
+            Scenario {
+                input: r"status in [404, 500.0]",
+                expected: true,
+            },
+            Scenario {
+                input: r"status in []",
+                expected: false,
+            },
+            Scenario {
+                input: r"level !in ['Info', 'Warn']",
+                expected: true,
+            },
+            Scenario {
+                input: r"user.name in [null]",
+                expected: true,
+            },
+            // any
+            Scenario {
+                input: r"tags ?= 'db'",
+                expected: true,
+            },
+            Scenario {
+                input: r"tags ?= 'http'",
+                expected: false,
+            },
+            Scenario {
+                input: r"tags ?!= 'db'",
+                expected: true,
+            },
+            Scenario {
+                input: r"tags ?~ 'time'",
+                expected: true,
+            },
+            Scenario {
+                input: r"tags ?!~ ''",
+                expected: false,
+            },
+            Scenario {
+                input: r"tags ?> 'x' || tags ?< 'D'",
+                expected: false,
+            },
+            Scenario {
+                input: r"status ?= 500 && missing ?= null",
+                expected: true,
+            },
+            // precedence and groups
+            Scenario {
+                input: r"status = 1 || status = 500 && level = 'Error'",
+                expected: true,
+            },
+            Scenario {
+                input: r"status = 500 || status = 1 && level = 'Info'",
+                expected: true,
+            },
+            Scenario {
+                input: r"(status = 500 || status = 1) && level = 'Info'",
+                expected: false,
+            },
+            Scenario {
+                input: r"!(status = 1) && !!(level = 'Error')",
+                expected: true,
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let filter = compile(&parse(scenario.input).unwrap()).unwrap();
+            let result = filter.matches(&entry);
+
+            assert!(
+                result == scenario.expected,
+                "({}) Expected {} for {}, got {}",
+                i,
+                scenario.expected,
+                scenario.input,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_compiled_filter_like() {
+        struct Scenario {
+            input: String,
+            expected: bool,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"'ÉCOLE Normale' ~ 'éco' && 'École' ~ 'COLE'".to_string(),
+                expected: true,
+            },
+            Scenario {
+                // U+212A (Kelvin sign) is lowercased to an ASCII `k`
+                input: "'\u{212A}elvin' ~ 'kel' && 'kelvin' ~ '\u{212A}'".to_string(),
+                expected: true,
+            },
+            Scenario {
+                // `ſ` is not lowercased to `s` (but it is case folded to it)
+                input: r"'ſ' ~ 's' || 's' ~ 'ſ'".to_string(),
+                expected: false,
+            },
+            Scenario {
+                input: r"'abc' ~ 'é' || 'ABC' ~ 'abcd'".to_string(),
+                expected: false,
+            },
+            Scenario {
+                // too large for a regex
+                input: format!("'{}' ~ '{}'", "É".repeat(200_000), "é".repeat(200_000)),
+                expected: true,
+            },
+            Scenario {
+                // field patterns and non-text operands
+                input: "level ~ 'über' && 'ALL ÜBER' ~ level && status ~ 50 && '1503' ~ status && duration ~ '.5' && true ~ 'RU'".to_string(),
+                expected: true,
+            },
+            Scenario {
+                input: "level ~ status || status ~ duration || duration ~ 15".to_string(),
+                expected: false,
+            },
+        ];
+
+        let entry = Entry {
+            level: "Über",
+            status: 503,
+            duration: 1.5,
+            user: None,
+            tags: vec![],
+        };
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let filter = compile(&parse(&scenario.input).unwrap()).unwrap();
+            let result = filter.matches(&entry);
+
+            assert!(
+                result == scenario.expected,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        fn expr(op: SignOp, right: Token<'static>) -> ExprGroups<'static> {
+            ExprGroups::from(vec![ExprGroup::new(
+                JoinOp::And,
+                Expr::new(Token::Identifier("a".into()), op, right),
+            )])
+        }
+
+        let scenarios = [
+            (ExprGroups::new(), ErrorKind::Empty),
+            (
+                expr(SignOp::None, Token::Number("1".into())),
+                ErrorKind::Incomplete,
+            ),
+            (
+                expr(SignOp::In, Token::Number("1".into())),
+                ErrorKind::UnsupportedOperand,
+            ),
+            (
+                expr(SignOp::Eq, Token::List(vec![])),
+                ErrorKind::UnsupportedOperand,
+            ),
+            (
+                expr(SignOp::Like, Token::List(vec![])),
+                ErrorKind::UnsupportedOperand,
+            ),
+            (
+                expr(SignOp::Eq, Token::Number("x".into())),
+                ErrorKind::InvalidNumber,
+            ),
+        ];
+
+        for (i, (ast, kind)) in scenarios.iter().enumerate() {
+            match compile(ast) {
+                Ok(_) => panic!("({}) Expected error", i),
+                Err(err) => assert!(
+                    err.kind == *kind,
+                    "({}) Expected {:?}, got {:?}",
+                    i,
+                    kind,
+                    err.kind
+                ),
+            }
+        }
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_filterable() {
+        use crate::filter::{compile_for, Filterable};
+
+        #[derive(Filterable)]
+        struct Author {
+            name: String,
+            #[filter(rename = "mail")]
+            email: Option<String>,
+        }
+
+        #[derive(Filterable)]
+        struct Comment {
+            likes: u32,
+        }
+
+        #[derive(Filterable)]
+        struct Post<'a> {
+            id: u64,
+            title: &'a str,
+            rating: f32,
+            published: bool,
+            author: Author,
+            editor: Option<Author>,
+            tags: Vec<String>,
+            comments: Vec<Comment>,
+            #[filter(skip)]
+            #[allow(dead_code)]
+            secret: String,
+        }
+
+        struct Scenario {
+            input: &'static str,
+            expected: bool,
+        }
+
+        let post = Post {
+            id: 7,
+            title: "Hello",
+            rating: 4.5,
+            published: true,
+            author: Author {
+                name: "Jane".to_string(),
+                email: None,
+            },
+            editor: None,
+            tags: vec!["rust".to_string(), "Parser".to_string()],
+            comments: vec![Comment { likes: 1 }, Comment { likes: 10 }],
+            secret: "xyz".to_string(),
+        };
+
+        let scenarios = [
+            Scenario {
+                input: r"id = 7 && id > 6.5 && title ~ 'ell'",
+                expected: true,
+            },
+            Scenario {
+                input: r"rating >= 4.5 && published = true",
+                expected: true,
+            },
+            Scenario {
+                input: r"author.name = 'Jane' && author.mail = null",
+                expected: true,
+            },
+            Scenario {
+                input: r"author.email = null && author.email != null",
+                expected: false,
+            },
+            Scenario {
+                input: r"editor = null && editor.name = null",
+                expected: true,
+            },
+            Scenario {
+                input: r"tags ?= 'rust' && tags ?~ 'PARSE'",
+                expected: true,
+            },
+            Scenario {
+                input: r"tags ?= 'go' || tags = null || comments = null",
+                expected: false,
+            },
+            Scenario {
+                input: r"tags != null && comments != null && author != null",
+                expected: true,
+            },
+            Scenario {
+                input: r"tags != 'rust' && tags !in ['rust'] && author !~ ''",
+                expected: true,
+            },
+            Scenario {
+                input: r"comments.likes ?> 5 && comments.likes ?< 5",
+                expected: true,
+            },
+            Scenario {
+                input: r"comments.likes ?> 10",
+                expected: false,
+            },
+            Scenario {
+                input: r"secret = 'xyz'",
+                expected: false,
+            },
+            Scenario {
+                input: r"author = null || author ?= null",
+                expected: false,
+            },
+            Scenario {
+                input: r"unknown = null && unknown ?= null",
+                expected: true,
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let filter = compile(&parse(scenario.input).unwrap()).unwrap();
+            let result = filter.matches(&post);
+
+            assert!(
+                result == scenario.expected,
+                "({}) Expected {} for {}, got {}",
+                i,
+                scenario.expected,
+                scenario.input,
+                result
+            );
+        }
+
+        let path = ["author".to_string(), "name".to_string()];
+        assert!(post.filter_get(&path) == Some(Value::from("Jane")));
+
+        // type checked compilation
+        let scenarios = [
+            (r"id = 'x'", Some("x")),
+            (r"title > 1 || author.name in ['a', 1]", Some("1")),
+            (r"'x' < rating", Some("x")),
+            (r"published = rating", Some("rating")),
+            (r"editor.mail ~ false", Some("false")),
+            (r"tags = 'rust'", Some("rust")),
+            (r"comments.likes ?> 'x'", Some("x")),
+            (r"id > null", Some("null")),
+            (
+                r"id in [1, null] && tags ?= 'x' && comments.likes ?> 5 && editor.mail = null && author ~ 1 && unknown = 'x'",
+                None,
+            ),
+        ];
+
+        for (i, (input, expected)) in scenarios.iter().enumerate() {
+            let result = compile_for::<Post>(&parse(input).unwrap())
+                .err()
+                .map(|err| (err.kind, err.token.literal().to_string()));
+            let expected = expected.map(|literal| (ErrorKind::TypeMismatch, literal.to_string()));
+
+            assert!(
+                result == expected,
+                "({}) Expected {:?} for {}, got {:?}",
+                i,
+                expected,
+                input,
+                result
+            );
+        }
+
+        let filter = compile_for::<Post>(&parse("comments.likes ?> 5").unwrap()).unwrap();
+        assert!(filter.matches(&post), "Expected the typed filter to match");
+    }
+}
@@ -0,0 +1,748 @@
+use crate::{
+    error::{Error, ErrorKind},
+    parser::{is_operand, Expr, ExprGroupItem, ExprGroups},
+    scanner::{JoinOp, Scanner, SignOp, Token},
+    span::Span,
+    tree::ExprTree,
+};
+
+// format formats the provided AST into a canonical fexpr text
+// using the default `Formatter` options.
+pub fn format(expr_groups: &ExprGroups) -> Result<String, Error> {
+    Formatter::new().format(expr_groups)
+}
+
+// Formatter formats an AST back into a valid canonical fexpr text,
+// so that parsing the formatted text results in the same AST.
+//
+// The formatted text is parsed back into the same AST with the same
+// normalizations as the parser applies:
+//   - the first group join operator of each `ExprGroups` is omitted
+//     (the parser always sets it to `&&`, so an AST starting with
+//     a `||` join is parsed back with `&&`)
+//   - directly nested negations are collapsed (eg. `!!a=1` is formatted as `a=1`)
+//
+// The nested groups are always wrapped in parenthesis since they are part of the AST.
+//
+// Text tokens are wrapped in single quotes, unless the text contains
+// only single and no double quotes. A text ending with `\` or containing
+// the NUL character couldn't be represented and results in an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Formatter {
+    sign_spacing: bool,
+    join_spacing: bool,
+    list_spacing: bool,
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Self {
+            sign_spacing: true,
+            join_spacing: true,
+            list_spacing: true,
+        }
+    }
+}
+
+impl Formatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // with_sign_spacing specifies whether to surround the sign operators
+    // with whitespaces (eg. `a = 1` vs `a=1`).
+    //
+    // The `in` and `!in` operators are always surrounded with whitespaces.
+    pub fn with_sign_spacing(mut self, sign_spacing: bool) -> Self {
+        self.sign_spacing = sign_spacing;
+        self
+    }
+
+    // with_join_spacing specifies whether to surround the join operators
+    // with whitespaces (eg. `a=1 && b=2` vs `a=1&&b=2`).
+    pub fn with_join_spacing(mut self, join_spacing: bool) -> Self {
+        self.join_spacing = join_spacing;
+        self
+    }
+
+    // with_list_spacing specifies whether to add a whitespace after
+    // each list item separator (eg. `[1, 2]` vs `[1,2]`).
+    pub fn with_list_spacing(mut self, list_spacing: bool) -> Self {
+        self.list_spacing = list_spacing;
+        self
+    }
+
+    // format formats the provided AST into a fexpr text.
+    pub fn format(&self, expr_groups: &ExprGroups) -> Result<String, Error> {
+        let mut out = String::new();
+        self.write_groups(&mut out, expr_groups, Span::default())?;
+        Ok(out)
+    }
+
+    // format_tree formats the provided expression tree into a fexpr text
+    // with the minimal number of parenthesis required to preserve its shape
+    // (aka. `ExprTree::from_groups` of the parsed text results in the same tree).
+    pub fn format_tree(&self, tree: &ExprTree) -> Result<String, Error> {
+        let mut out = String::new();
+        self.write_tree(&mut out, tree)?;
+        Ok(out)
+    }
+
+    fn write_groups(
+        &self,
+        out: &mut String,
+        expr_groups: &ExprGroups,
+        span: Span,
+    ) -> Result<(), Error> {
+        if expr_groups.is_empty() {
+            return Err(Error::new(ErrorKind::Empty, Token::None, span));
+        }
+
+        for (i, expr_group) in expr_groups.iter().enumerate() {
+            if i > 0 {
+                self.write_join(out, expr_group.join);
+            }
+            self.write_item(out, &expr_group.item, expr_group.span)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_item(&self, out: &mut String, item: &ExprGroupItem, span: Span) -> Result<(), Error> {
+        match item {
+            ExprGroupItem::Expr(expr) => self.write_expr(out, expr),
+            ExprGroupItem::ExprGroups(expr_groups) => {
+                out.push('(');
+                self.write_groups(out, expr_groups, span)?;
+                out.push(')');
+                Ok(())
+            }
+            ExprGroupItem::Not(_) => {
+                // directly nested negations are collapsed (similar to the parser)
+                let mut item = item;
+                let mut negated = false;
+                while let ExprGroupItem::Not(operand) = item {
+                    negated = !negated;
+                    item = operand;
+                }
+
+                if negated {
+                    out.push('!');
+                }
+                self.write_item(out, item, span)
+            }
+        }
+    }
+
+    fn write_tree(&self, out: &mut String, tree: &ExprTree) -> Result<(), Error> {
+        match tree {
+            // `&&` is left associative and binds tighter than `||`
+            ExprTree::And(left, right) => {
+                self.write_subtree(out, left, matches!(**left, ExprTree::Or(..)))?;
+                self.write_join(out, JoinOp::And);
+                self.write_subtree(
+                    out,
+                    right,
+                    matches!(**right, ExprTree::And(..) | ExprTree::Or(..)),
+                )
+            }
+            // `||` is left associative
+            ExprTree::Or(left, right) => {
+                self.write_subtree(out, left, false)?;
+                self.write_join(out, JoinOp::Or);
+                self.write_subtree(out, right, matches!(**right, ExprTree::Or(..)))
+            }
+            ExprTree::Not(_) => {
+                // directly nested negations are collapsed (similar to the parser)
+                let mut operand = tree;
+                let mut negated = false;
+                while let ExprTree::Not(inner) = operand {
+                    negated = !negated;
+                    operand = inner;
+                }
+
+                if negated {
+                    out.push('!');
+                }
+
+                // the parent doesn't wrap a negation so the operand is always wrapped
+                self.write_subtree(
+                    out,
+                    operand,
+                    matches!(*operand, ExprTree::And(..) | ExprTree::Or(..)),
+                )
+            }
+            ExprTree::Comparison(expr) => self.write_expr(out, expr),
+        }
+    }
+
+    fn write_subtree(&self, out: &mut String, tree: &ExprTree, wrap: bool) -> Result<(), Error> {
+        if wrap {
+            out.push('(');
+        }
+        self.write_tree(out, tree)?;
+        if wrap {
+            out.push(')');
+        }
+        Ok(())
+    }
+
+    fn write_join(&self, out: &mut String, join: JoinOp) {
+        if self.join_spacing {
+            out.push(' ');
+        }
+        out.push_str(&join.to_string());
+        if self.join_spacing {
+            out.push(' ');
+        }
+    }
+
+    fn write_expr(&self, out: &mut String, expr: &Expr) -> Result<(), Error> {
+        if !is_operand(&expr.left) {
+            return Err(
+                Error::new(ErrorKind::UnexpectedToken, expr.left.clone(), expr.span)
+                    .with_expected(&["identifier", "text", "number", "bool", "null"]),
+            );
+        }
+
+        let membership = matches!(expr.op, SignOp::In | SignOp::Nin);
+
+        if membership && !matches!(expr.right, Token::List(_)) {
+            return Err(
+                Error::new(ErrorKind::UnexpectedToken, expr.right.clone(), expr.span)
+                    .with_expected(&["list"]),
+            );
+        }
+
+        if !membership && !is_operand(&expr.right) {
+            return Err(
+                Error::new(ErrorKind::UnexpectedToken, expr.right.clone(), expr.span)
+                    .with_expected(&["identifier", "text", "number", "bool", "null"]),
+            );
+        }
+
+        if expr.op == SignOp::None {
+            return Err(
+                Error::new(ErrorKind::Incomplete, Token::None, expr.span).with_expected(&["sign"])
+            );
+        }
+
+        self.write_token(out, &expr.left, expr.span)?;
+        if self.sign_spacing || membership {
+            out.push(' ');
+        }
+        out.push_str(&expr.op.to_string());
+        if self.sign_spacing || membership {
+            out.push(' ');
+        }
+        self.write_token(out, &expr.right, expr.span)
+    }
+
+    fn write_token(&self, out: &mut String, token: &Token, span: Span) -> Result<(), Error> {
+        if let Token::List(items) = token {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if matches!(item, Token::Identifier(_) | Token::List(_)) || !is_operand(item) {
+                    return Err(Error::new(ErrorKind::InvalidList, token.clone(), span));
+                }
+                if i > 0 {
+                    out.push(',');
+                    if self.list_spacing {
+                        out.push(' ');
+                    }
+                }
+                self.write_token(out, item, span)?;
+            }
+            out.push(']');
+            return Ok(());
+        }
+
+        let formatted = match token {
+            Token::Text(value) => quote(value),
+            _ => token.literal().to_string(),
+        };
+
+        // ensure that the formatted token is scanned back to the same token
+        let mut scanner = Scanner::new(&formatted);
+        let valid = match (scanner.scan(), scanner.scan()) {
+            (Ok(scanned), Ok(Token::Eof(_))) => &scanned == token,
+            _ => false,
+        };
+        if !valid {
+            let kind = match token {
+                Token::Identifier(_) => ErrorKind::InvalidIdentifier,
+                Token::Number(_) => ErrorKind::InvalidNumber,
+                Token::Text(_) => ErrorKind::InvalidText,
+                _ => ErrorKind::UnexpectedToken,
+            };
+            return Err(Error::new(kind, token.clone(), span));
+        }
+
+        out.push_str(&formatted);
+
+        Ok(())
+    }
+}
+
+// quote wraps the text value in quotes, escaping the matching quotes.
+fn quote(value: &str) -> String {
+    let quote = if value.contains('\'') && !value.contains('"') {
+        '"'
+    } else {
+        '\''
+    };
+
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push(quote);
+    for ch in value.chars() {
+        if ch == quote {
+            result.push('\\');
+        }
+        result.push(ch);
+    }
+    result.push(quote);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::{
+        error::ErrorKind,
+        format::{format, Formatter},
+        parser::{parse, Expr, ExprGroup, ExprGroupItem, ExprGroups},
+        scanner::{JoinOp, SignOp, Token},
+        tree::ExprTree,
+    };
+
+    #[test]
+    fn test_format() {
+        struct Scenario {
+            input: &'static str,
+            formatter: Formatter,
+            expected: &'static str,
+        }
+
+        let compact = Formatter::new()
+            .with_sign_spacing(false)
+            .with_join_spacing(false)
+            .with_list_spacing(false);
+
+        let scenarios = [
+            Scenario {
+                input: r"a=1",
+                formatter: Formatter::new(),
+                expected: r"a = 1",
+            },
+            Scenario {
+                input: "a>-1&&\n// comment\n b ?!~ 'c' ||c=true",
+                formatter: Formatter::new(),
+                expected: r"a > -1 && b ?!~ 'c' || c = true",
+            },
+            Scenario {
+                input: r"(a=1) && ((b = null || !c != d.e) && !!(f ~ 'g'))",
+                formatter: Formatter::new(),
+                expected: r"(a = 1) && ((b = null || !c != d.e) && (f ~ 'g'))",
+            },
+            Scenario {
+                input: r#"a = "b" && c = "d'e" && f = 'g\'h"i' && j = "\\'" && k = '日本'"#,
+                formatter: Formatter::new(),
+                expected: r#"a = 'b' && c = "d'e" && f = 'g\'h"i' && j = "\\'" && k = '日本'"#,
+            },
+            Scenario {
+                input: r"a in [ 1,'b' , null ] || c !in []",
+                formatter: Formatter::new(),
+                expected: r"a in [1, 'b', null] || c !in []",
+            },
+            Scenario {
+                input: r"a = 1 && !(b ~ 'c' || d in [1, 2]) || e >= -2.5",
+                formatter: compact,
+                expected: r"a=1&&!(b~'c'||d in [1,2])||e>=-2.5",
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let ast = parse(scenario.input).unwrap();
+            let result = scenario.formatter.format(&ast).unwrap();
+
+            assert!(
+                result == scenario.expected,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected,
+                result
+            );
+
+            let reparsed = parse(&result).unwrap();
+            assert!(
+                reparsed == ast,
+                "({}) Expected {}, got {}",
+                i,
+                ast,
+                reparsed
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_tree() {
+        struct Scenario {
+            input: &'static str,
+            expected: &'static str,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"((a=1))",
+                expected: r"a = 1",
+            },
+            Scenario {
+                input: r"(a=1 && b=2) || (c=3 && d=4)",
+                expected: r"a = 1 && b = 2 || c = 3 && d = 4",
+            },
+            Scenario {
+                input: r"a=1 && (b=2 || c=3)",
+                expected: r"a = 1 && (b = 2 || c = 3)",
+            },
+            Scenario {
+                input: r"a=1 && (b=2 && c=3)",
+                expected: r"a = 1 && (b = 2 && c = 3)",
+            },
+            Scenario {
+                input: r"(a=1 || b=2) || (c=3 || d=4)",
+                expected: r"a = 1 || b = 2 || (c = 3 || d = 4)",
+            },
+            Scenario {
+                input: r"!(a=1) || !!(b=2 && c=3)",
+                expected: r"!a = 1 || b = 2 && c = 3",
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let tree = ExprTree::from_groups(&parse(scenario.input).unwrap()).unwrap();
+            let result = Formatter::new().format_tree(&tree).unwrap();
+
+            assert!(
+                result == scenario.expected,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_errors() {
+        fn expr(left: Token<'static>, op: SignOp, right: Token<'static>) -> ExprGroups<'static> {
+            ExprGroups::from(vec![ExprGroup::new(
+                JoinOp::And,
+                Expr::new(left, op, right),
+            )])
+        }
+
+        struct Scenario {
+            ast: ExprGroups<'static>,
+            kind: ErrorKind,
+        }
+
+        let scenarios = [
+            Scenario {
+                ast: ExprGroups::new(),
+                kind: ErrorKind::Empty,
+            },
+            Scenario {
+                ast: ExprGroups::from(vec![ExprGroup::new(JoinOp::And, ExprGroups::new())]),
+                kind: ErrorKind::Empty,
+            },
+            Scenario {
+                ast: expr(
+                    Token::Identifier("a".into()),
+                    SignOp::None,
+                    Token::Number("1".into()),
+                ),
+                kind: ErrorKind::Incomplete,
+            },
+            Scenario {
+                ast: expr(Token::None, SignOp::Eq, Token::Number("1".into())),
+                kind: ErrorKind::UnexpectedToken,
+            },
+            Scenario {
+                ast: expr(
+                    Token::Identifier("a".into()),
+                    SignOp::In,
+                    Token::Number("1".into()),
+                ),
+                kind: ErrorKind::UnexpectedToken,
+            },
+            Scenario {
+                ast: expr(
+                    Token::Identifier("a".into()),
+                    SignOp::Eq,
+                    Token::List(vec![]),
+                ),
+                kind: ErrorKind::UnexpectedToken,
+            },
+            Scenario {
+                ast: expr(
+                    Token::Identifier("a".into()),
+                    SignOp::In,
+                    Token::List(vec![Token::Identifier("b".into())]),
+                ),
+                kind: ErrorKind::InvalidList,
+            },
+            Scenario {
+                ast: expr(
+                    Token::Identifier("a b".into()),
+                    SignOp::Eq,
+                    Token::Number("1".into()),
+                ),
+                kind: ErrorKind::InvalidIdentifier,
+            },
+            Scenario {
+                ast: expr(
+                    Token::Identifier("in".into()),
+                    SignOp::Eq,
+                    Token::Number("1".into()),
+                ),
+                kind: ErrorKind::InvalidIdentifier,
+            },
+            Scenario {
+                ast: expr(
+                    Token::Identifier("a".into()),
+                    SignOp::Eq,
+                    Token::Number("1.".into()),
+                ),
+                kind: ErrorKind::InvalidNumber,
+            },
+            Scenario {
+                ast: expr(
+                    Token::Identifier("a".into()),
+                    SignOp::Eq,
+                    Token::Text(r"b\".into()),
+                ),
+                kind: ErrorKind::InvalidText,
+            },
+            Scenario {
+                ast: expr(
+                    Token::Identifier("a".into()),
+                    SignOp::Eq,
+                    Token::Text("b\0".into()),
+                ),
+                kind: ErrorKind::InvalidText,
+            },
+            Scenario {
+                ast: expr(
+                    Token::Identifier("a".into()),
+                    SignOp::Eq,
+                    Token::Bool("yes".into()),
+                ),
+                kind: ErrorKind::UnexpectedToken,
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            match format(&scenario.ast) {
+                Ok(result) => panic!("({}) Expected error, got {}", i, result),
+                Err(err) => assert!(
+                    err.kind == scenario.kind,
+                    "({}) Expected {:?}, got {:?}",
+                    i,
+                    scenario.kind,
+                    err.kind
+                ),
+            }
+        }
+    }
+
+    fn operand() -> impl Strategy<Value = Token<'static>> {
+        prop_oneof![
+            "[@#_]?[a-zA-Z][a-zA-Z0-9_]{0,6}(\\.[a-z0-9:]{0,3}[a-z0-9])?"
+                .prop_filter("keyword", |s| !matches!(
+                    s.as_str(),
+                    "in" | "true" | "false" | "null"
+                ))
+                .prop_map(|s| Token::Identifier(s.into())),
+            "-?[0-9]{1,4}(\\.[0-9]{1,3})?".prop_map(|s| Token::Number(s.into())),
+            "[^\\x00]{0,8}"
+                .prop_filter("trailing backslash", |s| !s.ends_with('\\'))
+                .prop_map(|s| Token::Text(s.into())),
+            prop_oneof![Just("true"), Just("false")].prop_map(|s| Token::Bool(s.into())),
+            Just(Token::Null("null".into())),
+        ]
+    }
+
+    fn list() -> impl Strategy<Value = Token<'static>> {
+        prop::collection::vec(
+            operand().prop_filter("identifier", |t| !matches!(t, Token::Identifier(_))),
+            0..4,
+        )
+        .prop_map(Token::List)
+    }
+
+    fn expr() -> impl Strategy<Value = Expr<'static>> {
+        let comparison = (
+            operand(),
+            prop::sample::select(vec![
+                SignOp::Eq,
+                SignOp::Neq,
+                SignOp::Like,
+                SignOp::Nlike,
+                SignOp::Lt,
+                SignOp::Lte,
+                SignOp::Gt,
+                SignOp::Gte,
+                SignOp::AnyEq,
+                SignOp::AnyNeq,
+                SignOp::AnyLike,
+                SignOp::AnyNlike,
+                SignOp::AnyLt,
+                SignOp::AnyLte,
+                SignOp::AnyGt,
+                SignOp::AnyGte,
+            ]),
+            operand(),
+        );
+        let membership = (
+            operand(),
+            prop::sample::select(vec![SignOp::In, SignOp::Nin]),
+            list(),
+        );
+
+        prop_oneof![comparison, membership].prop_map(|(left, op, right)| Expr::new(left, op, right))
+    }
+
+    fn join() -> impl Strategy<Value = JoinOp> {
+        prop_oneof![Just(JoinOp::And), Just(JoinOp::Or)]
+    }
+
+    fn expr_groups() -> impl Strategy<Value = ExprGroups<'static>> {
+        let item = expr()
+            .prop_map(ExprGroupItem::Expr)
+            .prop_recursive(4, 32, 4, |item| {
+                prop_oneof![
+                    item.clone()
+                        .prop_map(|item| ExprGroupItem::Not(Box::new(item))),
+                    prop::collection::vec((join(), item), 1..4)
+                        .prop_map(|items| { ExprGroupItem::ExprGroups(groups_from(items)) }),
+                ]
+            });
+
+        prop::collection::vec((join(), item), 1..5).prop_map(groups_from)
+    }
+
+    // groups_from creates groups from the provided items
+    // (the first join could be also `||`, unlike the parsed ones).
+    fn groups_from(items: Vec<(JoinOp, ExprGroupItem<'static>)>) -> ExprGroups<'static> {
+        items
+            .into_iter()
+            .map(|(join, item)| ExprGroup::new(join, item))
+            .collect()
+    }
+
+    // normalize normalizes the AST similar to the parser - the first join
+    // of each groups is set to `&&` and the directly nested negations are collapsed.
+    fn normalize(expr_groups: ExprGroups<'static>) -> ExprGroups<'static> {
+        fn collapse(item: ExprGroupItem<'static>) -> ExprGroupItem<'static> {
+            match item {
+                ExprGroupItem::Not(operand) => match collapse(*operand) {
+                    ExprGroupItem::Not(operand) => *operand,
+                    operand => ExprGroupItem::Not(Box::new(operand)),
+                },
+                ExprGroupItem::ExprGroups(nested) => ExprGroupItem::ExprGroups(normalize(nested)),
+                item => item,
+            }
+        }
+
+        expr_groups
+            .into_iter()
+            .enumerate()
+            .map(|(i, expr_group)| {
+                let join = if i == 0 { JoinOp::And } else { expr_group.join };
+                ExprGroup::new(join, collapse(expr_group.item))
+            })
+            .collect()
+    }
+
+    // collapse_tree_negations collapses the directly nested tree negations.
+    fn collapse_tree_negations(tree: ExprTree<'static>) -> ExprTree<'static> {
+        match tree {
+            ExprTree::And(left, right) => ExprTree::and(
+                collapse_tree_negations(*left),
+                collapse_tree_negations(*right),
+            ),
+            ExprTree::Or(left, right) => ExprTree::or(
+                collapse_tree_negations(*left),
+                collapse_tree_negations(*right),
+            ),
+            ExprTree::Not(operand) => match collapse_tree_negations(*operand) {
+                ExprTree::Not(operand) => *operand,
+                operand => !operand,
+            },
+            tree => tree,
+        }
+    }
+
+    fn tree() -> impl Strategy<Value = ExprTree<'static>> {
+        expr()
+            .prop_map(ExprTree::Comparison)
+            .prop_recursive(4, 32, 2, |tree| {
+                prop_oneof![
+                    (tree.clone(), tree.clone()).prop_map(|(l, r)| ExprTree::and(l, r)),
+                    (tree.clone(), tree.clone()).prop_map(|(l, r)| ExprTree::or(l, r)),
+                    tree.prop_map(|t| !t),
+                ]
+            })
+    }
+
+    fn formatter() -> impl Strategy<Value = Formatter> {
+        (any::<bool>(), any::<bool>(), any::<bool>()).prop_map(|(sign, join, list)| {
+            Formatter::new()
+                .with_sign_spacing(sign)
+                .with_join_spacing(join)
+                .with_list_spacing(list)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_format_roundtrip(ast in expr_groups(), formatter in formatter()) {
+            let text = formatter.format(&ast).unwrap();
+            let parsed = parse(&text);
+            prop_assert!(parsed.is_ok(), "Failed to parse {}: {:?}", text, parsed);
+            prop_assert_eq!(parsed.unwrap(), normalize(ast), "{}", text);
+        }
+
+        #[test]
+        fn test_format_tree_roundtrip(tree in tree(), formatter in formatter()) {
+            let text = formatter.format_tree(&tree).unwrap();
+            let parsed = parse(&text);
+            prop_assert!(parsed.is_ok(), "Failed to parse {}: {:?}", text, parsed);
+            prop_assert_eq!(
+                ExprTree::from_groups(&parsed.unwrap()),
+                Some(collapse_tree_negations(tree)),
+                "{}",
+                text
+            );
+        }
+
+        #[test]
+        fn test_format_text(value in any::<String>()) {
+            let ast = ExprGroups::from(vec![ExprGroup::new(
+                JoinOp::And,
+                Expr::new(Token::Identifier("a".into()), SignOp::Eq, Token::Text(value.clone().into())),
+            )]);
+
+            // either an explicit error or a text that is parsed back to the same value
+            match format(&ast) {
+                Ok(text) => prop_assert_eq!(parse(&text).unwrap(), ast),
+                Err(err) => prop_assert!(
+                    err.kind == ErrorKind::InvalidText && (value.ends_with('\\') || value.contains('\0')),
+                    "Unexpected error {} for {:?}", err, value
+                ),
+            }
+        }
+    }
+}
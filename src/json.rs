@@ -0,0 +1,30 @@
+use serde_json::Value;
+
+use crate::{
+    error::{Error, ErrorKind},
+    scanner::Token,
+    span::Span,
+};
+
+// value converts a literal token into a JSON value.
+pub(crate) fn value(token: &Token, span: Span) -> Result<Value, Error> {
+    match token {
+        Token::Null(_) => Ok(Value::Null),
+        Token::Bool(value) => Ok(Value::Bool(value == "true")),
+        Token::Text(value) => Ok(Value::String(value.to_string())),
+        Token::Number(value) => match value.parse::<i64>() {
+            Ok(number) => Ok(number.into()),
+            Err(_) => value
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidNumber, token.clone(), span)),
+        },
+        _ => Err(Error::new(
+            ErrorKind::UnsupportedOperand,
+            token.clone(),
+            span,
+        )),
+    }
+}
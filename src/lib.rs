@@ -1,14 +1,72 @@
-mod bytes;
+// allows the derive macros to refer to the `::fexpr` paths in the crate tests
+#[cfg(all(test, feature = "derive"))]
+extern crate self as fexpr;
+
+#[cfg(feature = "elasticsearch")]
+pub mod elasticsearch;
 mod error;
+#[cfg(feature = "eval")]
+pub mod eval;
+pub mod filter;
+mod format;
+#[cfg(any(feature = "elasticsearch", feature = "mongodb", feature = "eval"))]
+mod json;
+#[cfg(feature = "mongodb")]
+pub mod mongodb;
+pub mod normal_form;
+pub mod optimize;
+mod params;
 mod parser;
 mod scanner;
+pub mod schema;
+#[cfg(feature = "serde")]
+mod serialize;
+mod span;
+pub mod sql;
+mod tree;
+pub mod visit;
 
 pub use error::Error;
+pub use error::ErrorKind;
+
+pub use format::format;
+pub use format::Formatter;
+
+pub use params::Param;
+pub use params::Params;
 
 pub use parser::parse;
+pub use parser::parse_with_options;
+pub use parser::parse_with_params;
+pub use parser::Expr;
+pub use parser::ExprGroup;
 pub use parser::ExprGroupItem;
+pub use parser::ExprGroups;
+pub use parser::ParseOptions;
+pub use parser::DEFAULT_MAX_DEPTH;
 
 pub use scanner::JoinOp;
 pub use scanner::Scanner;
 pub use scanner::SignOp;
 pub use scanner::Token;
+
+pub use span::Position;
+pub use span::Span;
+
+pub use tree::ExprTree;
+
+// serde_json is used only by the optional features (and their tests)
+#[cfg(all(
+    test,
+    not(any(
+        feature = "serde",
+        feature = "elasticsearch",
+        feature = "mongodb",
+        feature = "eval"
+    ))
+))]
+use serde_json as _;
+
+// stats_alloc is used only by the allocation counting integration test
+#[cfg(test)]
+use stats_alloc as _;
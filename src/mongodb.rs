@@ -0,0 +1,387 @@
+use serde_json::{json, Value};
+
+use crate::{
+    error::{Error, ErrorKind},
+    json::value,
+    parser::{Expr, ExprGroupItem, ExprGroups},
+    scanner::{JoinOp, SignOp, Token},
+    span::Span,
+};
+
+// compile compiles the provided AST into a MongoDB query filter document.
+//
+// Every identifier is resolved through the `fields` mapping which returns
+// the document field name (eg. `author.name`) for the identifier or `None`
+// if it is not allowed.
+//
+// The `&&` joined items are combined with `$and` and the `||` joined ones with `$or`.
+// Negations are compiled as `$nor` (MongoDB doesn't have a top level `$not`).
+//
+// The sign operators are compiled as follows:
+//   - `=`, `!=`, `<`, `<=`, `>`, `>=` - `$eq`, `$ne`, `$lt`, `$lte`, `$gt`, `$gte`
+//   - `~`, `!~` - case insensitive `$regex` with escaped input (and its `$not`)
+//   - `in`, `!in` - `$in` and `$nin`
+//   - `?=`, `?!=`, `?<`, ... `?~`, `?!~` - `$elemMatch` with the above operators
+//
+// Comparisons between two fields are compiled as `$expr` aggregation expressions.
+// If the field is on the right side of a comparison with a literal, the operands are swapped.
+// Null could be compared only with the (in)equality operators.
+pub fn compile<F>(expr_groups: &ExprGroups, fields: F) -> Result<Value, Error>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    Compiler { fields }.compile_groups(expr_groups, Span::default())
+}
+
+// combine combines the clauses with the logical operator
+// (a single clause is returned as it is).
+fn combine(op: &str, mut clauses: Vec<Value>) -> Value {
+    if clauses.len() == 1 {
+        return clauses.remove(0);
+    }
+
+    json!({ op: clauses })
+}
+
+// regex returns a case insensitive regex operator matching
+// anywhere in the field value (the special chars are escaped).
+fn regex(token: &Token, span: Span) -> Result<Value, Error> {
+    match token {
+        Token::Text(value) | Token::Number(value) | Token::Bool(value) => {
+            Ok(json!({"$regex": regex::escape(value), "$options": "i"}))
+        }
+        _ => Err(unsupported(token, span)),
+    }
+}
+
+struct Compiler<F> {
+    fields: F,
+}
+
+impl<F> Compiler<F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    fn compile_groups(&self, expr_groups: &ExprGroups, span: Span) -> Result<Value, Error> {
+        if expr_groups.is_empty() {
+            return Err(Error::new(ErrorKind::Empty, Token::None, span));
+        }
+
+        // `&&` binds tighter than `||`
+        let mut conjunctions: Vec<Vec<Value>> = Vec::new();
+        for (i, expr_group) in expr_groups.iter().enumerate() {
+            let clause = self.compile_item(&expr_group.item, expr_group.span)?;
+
+            match conjunctions.last_mut() {
+                Some(conjunction) if i == 0 || expr_group.join == JoinOp::And => {
+                    conjunction.push(clause)
+                }
+                _ => conjunctions.push(vec![clause]),
+            }
+        }
+
+        let disjunction = conjunctions
+            .into_iter()
+            .map(|clauses| combine("$and", clauses))
+            .collect();
+
+        Ok(combine("$or", disjunction))
+    }
+
+    fn compile_item(&self, item: &ExprGroupItem, span: Span) -> Result<Value, Error> {
+        match item {
+            ExprGroupItem::Expr(expr) => self.compile_expr(expr),
+            ExprGroupItem::ExprGroups(expr_groups) => self.compile_groups(expr_groups, span),
+            ExprGroupItem::Not(item) => Ok(json!({"$nor": [self.compile_item(item, span)?]})),
+        }
+    }
+
+    fn compile_expr(&self, expr: &Expr) -> Result<Value, Error> {
+        if expr.op == SignOp::None {
+            return Err(
+                Error::new(ErrorKind::Incomplete, Token::None, expr.span).with_expected(&["sign"])
+            );
+        }
+
+        let (left, op, right) = match (&expr.left, &expr.right) {
+            (Token::Identifier(_), Token::Identifier(_)) => return self.compile_fields_expr(expr),
+            // the field is expected to be on the left side
+            (left, Token::Identifier(_)) => match flip(expr.op) {
+                Some(op) => (&expr.right, op, left),
+                None => return Err(unsupported(left, expr.span)),
+            },
+            (left, right) => (left, expr.op, right),
+        };
+
+        let field = self.field(left, expr.span)?;
+
+        // null could be compared only for (in)equality (similar to the other compilers)
+        if matches!(right, Token::Null(_))
+            && matches!(
+                op,
+                SignOp::Lt
+                    | SignOp::Lte
+                    | SignOp::Gt
+                    | SignOp::Gte
+                    | SignOp::AnyLt
+                    | SignOp::AnyLte
+                    | SignOp::AnyGt
+                    | SignOp::AnyGte
+            )
+        {
+            return Err(unsupported(right, expr.span));
+        }
+
+        let condition = match op {
+            SignOp::Like | SignOp::AnyLike => regex(right, expr.span)?,
+            SignOp::Nlike | SignOp::AnyNlike => json!({"$not": regex(right, expr.span)?}),
+            SignOp::In | SignOp::Nin => {
+                let items = match right {
+                    Token::List(items) => items,
+                    _ => return Err(unsupported(right, expr.span).with_expected(&["list"])),
+                };
+                let values = items
+                    .iter()
+                    .map(|item| value(item, expr.span))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                json!({ operator(op): values })
+            }
+            _ => json!({ operator(op): value(right, expr.span)? }),
+        };
+
+        let condition = match op {
+            SignOp::AnyEq
+            | SignOp::AnyNeq
+            | SignOp::AnyLike
+            | SignOp::AnyNlike
+            | SignOp::AnyLt
+            | SignOp::AnyLte
+            | SignOp::AnyGt
+            | SignOp::AnyGte => json!({"$elemMatch": condition}),
+            _ => condition,
+        };
+
+        Ok(json!({ field: condition }))
+    }
+
+    // compile_fields_expr compiles a comparison of two fields as `$expr`.
+    fn compile_fields_expr(&self, expr: &Expr) -> Result<Value, Error> {
+        if !matches!(
+            expr.op,
+            SignOp::Eq | SignOp::Neq | SignOp::Lt | SignOp::Lte | SignOp::Gt | SignOp::Gte
+        ) {
+            return Err(unsupported(&expr.right, expr.span));
+        }
+
+        let left = format!("${}", self.field(&expr.left, expr.span)?);
+        let right = format!("${}", self.field(&expr.right, expr.span)?);
+
+        Ok(json!({"$expr": { operator(expr.op): [left, right] }}))
+    }
+
+    // field resolves the field name of an identifier token.
+    fn field(&self, token: &Token, span: Span) -> Result<String, Error> {
+        match token {
+            Token::Identifier(name) => (self.fields)(name)
+                .ok_or_else(|| Error::new(ErrorKind::UnknownIdentifier, token.clone(), span)),
+            _ => Err(unsupported(token, span).with_expected(&["identifier"])),
+        }
+    }
+}
+
+// operator returns the MongoDB comparison query operator of the sign operator.
+fn operator(op: SignOp) -> &'static str {
+    match op {
+        SignOp::Eq | SignOp::AnyEq => "$eq",
+        SignOp::Neq | SignOp::AnyNeq => "$ne",
+        SignOp::Lt | SignOp::AnyLt => "$lt",
+        SignOp::Lte | SignOp::AnyLte => "$lte",
+        SignOp::Gt | SignOp::AnyGt => "$gt",
+        SignOp::Gte | SignOp::AnyGte => "$gte",
+        SignOp::In => "$in",
+        SignOp::Nin => "$nin",
+        SignOp::None | SignOp::Like | SignOp::Nlike | SignOp::AnyLike | SignOp::AnyNlike => "",
+    }
+}
+
+// flip returns the operator with swapped operands
+// (only for the operators that could be swapped).
+fn flip(op: SignOp) -> Option<SignOp> {
+    match op {
+        SignOp::Eq => Some(SignOp::Eq),
+        SignOp::Neq => Some(SignOp::Neq),
+        SignOp::Lt => Some(SignOp::Gt),
+        SignOp::Lte => Some(SignOp::Gte),
+        SignOp::Gt => Some(SignOp::Lt),
+        SignOp::Gte => Some(SignOp::Lte),
+        _ => None,
+    }
+}
+
+fn unsupported(token: &Token, span: Span) -> Error {
+    Error::new(ErrorKind::UnsupportedOperand, token.clone(), span)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{error::ErrorKind, mongodb::compile, parser::parse};
+
+    fn fields(name: &str) -> Option<String> {
+        match name {
+            "a" | "b" | "tags" => Some(name.to_string()),
+            "author" => Some("author.name".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_compile() {
+        struct Scenario {
+            input: &'static str,
+            expected: Value,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"a = 1",
+                expected: json!({"a": {"$eq": 1}}),
+            },
+            Scenario {
+                input: r"a != 'x' && author < -1.5 && a <= true && b != null && b >= 2",
+                expected: json!({"$and": [
+                    {"a": {"$ne": "x"}},
+                    {"author.name": {"$lt": -1.5}},
+                    {"a": {"$lte": true}},
+                    {"b": {"$ne": null}},
+                    {"b": {"$gte": 2}},
+                ]}),
+            },
+            Scenario {
+                input: r"1 < a || 'x' = b",
+                expected: json!({"$or": [
+                    {"a": {"$gt": 1}},
+                    {"b": {"$eq": "x"}},
+                ]}),
+            },
+            Scenario {
+                input: r"a ~ 'x.*(y)' && b !~ 1",
+                expected: json!({"$and": [
+                    {"a": {"$regex": r"x\.\*\(y\)", "$options": "i"}},
+                    {"b": {"$not": {"$regex": "1", "$options": "i"}}},
+                ]}),
+            },
+            Scenario {
+                input: r"a in ['x', 1, null] && b !in []",
+                expected: json!({"$and": [
+                    {"a": {"$in": ["x", 1, null]}},
+                    {"b": {"$nin": []}},
+                ]}),
+            },
+            Scenario {
+                input: r"tags ?= 'x' && tags ?!= 'y' && tags ?> 1 && tags ?~ 'z' && tags ?!~ 'w'",
+                expected: json!({"$and": [
+                    {"tags": {"$elemMatch": {"$eq": "x"}}},
+                    {"tags": {"$elemMatch": {"$ne": "y"}}},
+                    {"tags": {"$elemMatch": {"$gt": 1}}},
+                    {"tags": {"$elemMatch": {"$regex": "z", "$options": "i"}}},
+                    {"tags": {"$elemMatch": {"$not": {"$regex": "w", "$options": "i"}}}},
+                ]}),
+            },
+            Scenario {
+                input: r"a = b || author >= a",
+                expected: json!({"$or": [
+                    {"$expr": {"$eq": ["$a", "$b"]}},
+                    {"$expr": {"$gte": ["$author.name", "$a"]}},
+                ]}),
+            },
+            Scenario {
+                input: r"a = 1 && (b = 2 || !(a = 3 && b = 4)) || !(!(a = 5))",
+                expected: json!({"$or": [
+                    {"$and": [
+                        {"a": {"$eq": 1}},
+                        {"$or": [
+                            {"b": {"$eq": 2}},
+                            {"$nor": [{"$and": [{"a": {"$eq": 3}}, {"b": {"$eq": 4}}]}]},
+                        ]},
+                    ]},
+                    {"$nor": [{"$nor": [{"a": {"$eq": 5}}]}]},
+                ]}),
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let result = compile(&parse(scenario.input).unwrap(), fields).unwrap();
+
+            assert!(
+                result == scenario.expected,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        struct Scenario {
+            input: &'static str,
+            kind: ErrorKind,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"unknown = 1",
+                kind: ErrorKind::UnknownIdentifier,
+            },
+            Scenario {
+                input: r"a = unknown",
+                kind: ErrorKind::UnknownIdentifier,
+            },
+            Scenario {
+                input: r"1 = 2",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"'x' ~ a",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"a ~ b",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"a ~ null",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"b > null",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"null <= b",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"tags ?< null",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            match compile(&parse(scenario.input).unwrap(), fields) {
+                Ok(result) => panic!("({}) Expected error, got {}", i, result),
+                Err(err) => assert!(
+                    err.kind == scenario.kind,
+                    "({}) Expected {:?}, got {:?}",
+                    i,
+                    scenario.kind,
+                    err.kind
+                ),
+            }
+        }
+    }
+}
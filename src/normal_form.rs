@@ -0,0 +1,448 @@
+use std::collections::HashSet;
+
+use crate::{
+    error::{Error, ErrorKind},
+    parser::{Expr, ExprGroup, ExprGroupItem, ExprGroups},
+    scanner::{JoinOp, Token},
+    span::Span,
+};
+
+// Form represents the kind of a normal form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Form {
+    // Conjunctive is a conjunction of disjunctive clauses (aka. `(a || b) && (c || d)`).
+    Conjunctive,
+    // Disjunctive is a disjunction of conjunctive clauses (aka. `a && b || c && d`).
+    Disjunctive,
+}
+
+// Literal represents a single (optionally negated) expression of a normal form clause.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Literal<'a> {
+    pub expr: Expr<'a>,
+    pub negated: bool,
+}
+
+impl<'a> Literal<'a> {
+    fn into_item(self) -> ExprGroupItem<'a> {
+        if self.negated {
+            ExprGroupItem::Not(Box::new(ExprGroupItem::Expr(self.expr)))
+        } else {
+            ExprGroupItem::Expr(self.expr)
+        }
+    }
+}
+
+// NormalForm represents an expression converted into a conjunctive
+// or disjunctive normal form (see `to_cnf` and `to_dnf`).
+//
+// The negations are pushed down to the individual expressions,
+// so each clause is a flat list of (optionally negated) expressions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalForm<'a> {
+    form: Form,
+    clauses: Vec<Vec<Literal<'a>>>,
+}
+
+impl<'a> NormalForm<'a> {
+    // form returns the kind of the normal form.
+    pub fn form(&self) -> Form {
+        self.form
+    }
+
+    // clauses returns the normal form clauses - conjunctions of
+    // literals for DNF or disjunctions of literals for CNF.
+    pub fn clauses(&self) -> &[Vec<Literal<'a>>] {
+        &self.clauses
+    }
+
+    // into_clauses consumes the normal form and returns its clauses.
+    pub fn into_clauses(self) -> Vec<Vec<Literal<'a>>> {
+        self.clauses
+    }
+
+    // into_groups converts the normal form back into expression groups
+    // (eg. for formatting or passing it to one of the compilers).
+    pub fn into_groups(self) -> ExprGroups<'a> {
+        let (clause_join, literal_join) = match self.form {
+            Form::Conjunctive => (JoinOp::And, JoinOp::Or),
+            Form::Disjunctive => (JoinOp::Or, JoinOp::And),
+        };
+
+        let wrap = self.form == Form::Conjunctive && self.clauses.len() > 1;
+        let mut expr_groups = ExprGroups::new();
+
+        for (i, clause) in self.clauses.into_iter().enumerate() {
+            let join = if i == 0 { JoinOp::And } else { clause_join };
+
+            // `&&` binds tighter than `||` so only the CNF clauses need to be wrapped
+            if wrap && clause.len() > 1 {
+                let nested = join_literals(clause, literal_join);
+                expr_groups.push(ExprGroup::new(join, nested));
+                continue;
+            }
+
+            for (j, literal) in clause.into_iter().enumerate() {
+                let join = if j == 0 { join } else { literal_join };
+                expr_groups.push(ExprGroup::new(join, literal.into_item()));
+            }
+        }
+
+        expr_groups
+    }
+}
+
+fn join_literals(literals: Vec<Literal>, join: JoinOp) -> ExprGroups {
+    literals
+        .into_iter()
+        .enumerate()
+        .map(|(i, literal)| {
+            ExprGroup::new(if i == 0 { JoinOp::And } else { join }, literal.into_item())
+        })
+        .collect()
+}
+
+// to_cnf converts the expression groups into a conjunctive normal form
+// (aka. `&&` joined clauses of `||` joined expressions).
+//
+// Distributing `||` over `&&` could grow the result exponentially,
+// so the conversion fails with `ErrorKind::TooManyClauses`
+// if it would produce more than `max_clauses` clauses.
+pub fn to_cnf<'a>(
+    expr_groups: &ExprGroups<'a>,
+    max_clauses: usize,
+) -> Result<NormalForm<'a>, Error> {
+    convert(expr_groups, Form::Conjunctive, max_clauses)
+}
+
+// to_dnf converts the expression groups into a disjunctive normal form
+// (aka. `||` joined clauses of `&&` joined expressions).
+//
+// Distributing `&&` over `||` could grow the result exponentially,
+// so the conversion fails with `ErrorKind::TooManyClauses`
+// if it would produce more than `max_clauses` clauses.
+pub fn to_dnf<'a>(
+    expr_groups: &ExprGroups<'a>,
+    max_clauses: usize,
+) -> Result<NormalForm<'a>, Error> {
+    convert(expr_groups, Form::Disjunctive, max_clauses)
+}
+
+fn convert<'a>(
+    expr_groups: &ExprGroups<'a>,
+    form: Form,
+    max_clauses: usize,
+) -> Result<NormalForm<'a>, Error> {
+    let converter = Converter { form, max_clauses };
+
+    // the top level groups don't have a span of their own
+    let span = match (expr_groups.get().first(), expr_groups.get().last()) {
+        (Some(first), Some(last)) => first.span.to(last.span),
+        _ => Span::default(),
+    };
+
+    Ok(NormalForm {
+        form,
+        clauses: converter.convert_groups(expr_groups, span, false)?,
+    })
+}
+
+struct Converter {
+    form: Form,
+    max_clauses: usize,
+}
+
+impl Converter {
+    // convert_groups returns the clauses of the expression groups.
+    //
+    // `negated` indicates whether the groups are under an odd number
+    // of negations (and the join operators should be swapped).
+    fn convert_groups<'a>(
+        &self,
+        expr_groups: &ExprGroups<'a>,
+        span: Span,
+        negated: bool,
+    ) -> Result<Vec<Vec<Literal<'a>>>, Error> {
+        if expr_groups.is_empty() {
+            return Err(Error::new(ErrorKind::Empty, Token::None, span));
+        }
+
+        // `&&` binds tighter than `||`
+        let mut conjunctions: Vec<Vec<&ExprGroup>> = Vec::new();
+        for (i, expr_group) in expr_groups.iter().enumerate() {
+            match conjunctions.last_mut() {
+                Some(conjunction) if i == 0 || expr_group.join == JoinOp::And => {
+                    conjunction.push(expr_group)
+                }
+                _ => conjunctions.push(vec![expr_group]),
+            }
+        }
+
+        // De Morgan's laws
+        let (outer, inner) = if negated {
+            (JoinOp::And, JoinOp::Or)
+        } else {
+            (JoinOp::Or, JoinOp::And)
+        };
+
+        let mut parts = Vec::with_capacity(conjunctions.len());
+        for conjunction in conjunctions {
+            let mut operands = Vec::with_capacity(conjunction.len());
+            for expr_group in conjunction {
+                operands.push(self.convert_item(&expr_group.item, expr_group.span, negated)?);
+            }
+            parts.push(self.combine(operands, inner, span)?);
+        }
+
+        self.combine(parts, outer, span)
+    }
+
+    fn convert_item<'a>(
+        &self,
+        item: &ExprGroupItem<'a>,
+        span: Span,
+        negated: bool,
+    ) -> Result<Vec<Vec<Literal<'a>>>, Error> {
+        match item {
+            ExprGroupItem::Expr(expr) => Ok(vec![vec![Literal {
+                expr: expr.clone(),
+                negated,
+            }]]),
+            ExprGroupItem::ExprGroups(expr_groups) => {
+                self.convert_groups(expr_groups, span, negated)
+            }
+            ExprGroupItem::Not(item) => self.convert_item(item, span, !negated),
+        }
+    }
+
+    // combine joins the operands clauses with the provided join operator.
+    //
+    // If the operator is the one that joins the clauses (`&&` for CNF and `||` for DNF)
+    // the clauses are just concatenated, otherwise the operator is distributed
+    // over the clauses (aka. their cartesian product).
+    //
+    // The literals of each clause are sorted, so that the duplicated clauses are
+    // removed regardless of their literals order, and the clauses absorbed
+    // by another clause are removed too (see `absorb`).
+    fn combine<'a>(
+        &self,
+        operands: Vec<Vec<Vec<Literal<'a>>>>,
+        join: JoinOp,
+        span: Span,
+    ) -> Result<Vec<Vec<Literal<'a>>>, Error> {
+        let clause_join = match self.form {
+            Form::Conjunctive => JoinOp::And,
+            Form::Disjunctive => JoinOp::Or,
+        };
+
+        let mut result: Vec<Vec<Literal>> = if join == clause_join {
+            let total = operands.iter().map(Vec::len).sum();
+            self.check(total, span)?;
+            operands.into_iter().flatten().collect()
+        } else {
+            let mut product = vec![Vec::new()];
+            for clauses in operands {
+                self.check(product.len().saturating_mul(clauses.len()), span)?;
+                product = product
+                    .iter()
+                    .flat_map(|left| {
+                        clauses.iter().map(move |right| {
+                            let mut clause: Vec<Literal> = left.clone();
+                            for literal in right {
+                                if !clause.contains(literal) {
+                                    clause.push(literal.clone());
+                                }
+                            }
+                            clause
+                        })
+                    })
+                    .collect();
+            }
+            product
+        };
+
+        for clause in &mut result {
+            clause.sort_by_cached_key(|literal| (literal.expr.to_string(), literal.negated));
+        }
+
+        let mut seen = HashSet::with_capacity(result.len());
+        result.retain(|clause| seen.insert(clause.clone()));
+
+        absorb(&mut result);
+
+        Ok(result)
+    }
+
+    fn check(&self, clauses: usize, span: Span) -> Result<(), Error> {
+        if clauses > self.max_clauses {
+            return Err(Error::new(ErrorKind::TooManyClauses, Token::None, span));
+        }
+
+        Ok(())
+    }
+}
+
+// absorb removes the clauses that contain all literals of another (shorter) clause
+// (aka. the absorption law, eg. `a || a && b` is `a` in DNF and `a && (a || b)` is `a` in CNF).
+//
+// The clauses are expected to be already deduplicated.
+fn absorb(clauses: &mut Vec<Vec<Literal>>) {
+    let absorbed: Vec<bool> = clauses
+        .iter()
+        .map(|clause| {
+            clauses.iter().any(|other| {
+                other.len() < clause.len() && other.iter().all(|literal| clause.contains(literal))
+            })
+        })
+        .collect();
+
+    let mut absorbed = absorbed.into_iter();
+    clauses.retain(|_| !absorbed.next().unwrap_or_default());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::ErrorKind,
+        format::format,
+        normal_form::{to_cnf, to_dnf, Form},
+        parser::{parse, ExprGroups},
+    };
+
+    #[test]
+    fn test_to_cnf_and_to_dnf() {
+        struct Scenario {
+            input: &'static str,
+            cnf: &'static str,
+            dnf: &'static str,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: "a = 1",
+                cnf: "a = 1",
+                dnf: "a = 1",
+            },
+            Scenario {
+                input: "a = 1 && b = 2 || c = 3",
+                cnf: "(a = 1 || c = 3) && (b = 2 || c = 3)",
+                dnf: "a = 1 && b = 2 || c = 3",
+            },
+            Scenario {
+                input: "a = 1 && (b = 2 || c = 3)",
+                cnf: "a = 1 && (b = 2 || c = 3)",
+                dnf: "a = 1 && b = 2 || a = 1 && c = 3",
+            },
+            Scenario {
+                input: "(a = 1 || b = 2) && (c = 3 || d = 4)",
+                cnf: "(a = 1 || b = 2) && (c = 3 || d = 4)",
+                dnf: "a = 1 && c = 3 || a = 1 && d = 4 || b = 2 && c = 3 || b = 2 && d = 4",
+            },
+            Scenario {
+                input: "!(a = 1 || b = 2 && !c = 3)",
+                cnf: "!a = 1 && (!b = 2 || c = 3)",
+                dnf: "!a = 1 && !b = 2 || !a = 1 && c = 3",
+            },
+            Scenario {
+                input: "a = 1 || a = 1 && b = 2",
+                cnf: "a = 1",
+                dnf: "a = 1",
+            },
+            Scenario {
+                input: "(a = 1 || b = 2) && (a = 1 || b = 2)",
+                cnf: "a = 1 || b = 2",
+                dnf: "a = 1 || b = 2",
+            },
+            Scenario {
+                input: "(b = 2 || a = 1) && (a = 1 || b = 2) && (c = 3 || b = 2 || a = 1)",
+                cnf: "a = 1 || b = 2",
+                dnf: "b = 2 || a = 1",
+            },
+            Scenario {
+                input: "b = 2 && !a = 1 || !a = 1 && b = 2 || c = 3 && b = 2 && !a = 1",
+                cnf: "b = 2 && !a = 1",
+                dnf: "!a = 1 && b = 2",
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let expr_groups = parse(scenario.input).unwrap();
+
+            let cnf = to_cnf(&expr_groups, 100).unwrap();
+            assert!(
+                cnf.form() == Form::Conjunctive,
+                "({}) Expected conjunctive form, got {:?}",
+                i,
+                cnf.form()
+            );
+            let result = format(&cnf.into_groups()).unwrap();
+            assert!(
+                result == scenario.cnf,
+                "({}) Expected CNF {}, got {}",
+                i,
+                scenario.cnf,
+                result
+            );
+
+            let dnf = to_dnf(&expr_groups, 100).unwrap();
+            assert!(
+                dnf.form() == Form::Disjunctive,
+                "({}) Expected disjunctive form, got {:?}",
+                i,
+                dnf.form()
+            );
+            let result = format(&dnf.into_groups()).unwrap();
+            assert!(
+                result == scenario.dnf,
+                "({}) Expected DNF {}, got {}",
+                i,
+                scenario.dnf,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_clauses() {
+        // (a0 || b0) && (a1 || b1) && ... results in 2^n DNF clauses
+        let input = (0..20)
+            .map(|i| format!("(a{i} = 1 || b{i} = 1)"))
+            .collect::<Vec<_>>()
+            .join(" && ");
+        let expr_groups = parse(&input).unwrap();
+
+        let result = to_dnf(&expr_groups, 1000).unwrap_err();
+        assert!(
+            result.kind == ErrorKind::TooManyClauses,
+            "Expected {:?}, got {:?}",
+            ErrorKind::TooManyClauses,
+            result.kind
+        );
+
+        let result = to_cnf(&expr_groups, 1000).unwrap();
+        assert!(
+            result.clauses().len() == 20,
+            "Expected 20 CNF clauses, got {}",
+            result.clauses().len()
+        );
+
+        let result = to_cnf(&expr_groups, 19).unwrap_err();
+        assert!(
+            result.kind == ErrorKind::TooManyClauses,
+            "Expected {:?}, got {:?}",
+            ErrorKind::TooManyClauses,
+            result.kind
+        );
+
+        let result = to_dnf(&parse("(a = 1 || b = 1) && (c = 1 || d = 1)").unwrap(), 4);
+        assert!(result.is_ok(), "Expected 4 DNF clauses, got {:?}", result);
+
+        let result = to_dnf(&ExprGroups::new(), 4).unwrap_err();
+        assert!(
+            result.kind == ErrorKind::Empty,
+            "Expected {:?}, got {:?}",
+            ErrorKind::Empty,
+            result.kind
+        );
+    }
+}
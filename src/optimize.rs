@@ -0,0 +1,597 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    parser::{Expr, ExprGroup, ExprGroupItem, ExprGroups},
+    scanner::{JoinOp, SignOp, Token},
+    schema::flip,
+};
+
+// Optimized represents the result of an optimization pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Optimized<'a> {
+    // Groups holds the simplified expression groups.
+    Groups(ExprGroups<'a>),
+    // Always indicates that the filter matches everything
+    // (eg. there are no expressions to match).
+    Always,
+    // Never indicates that the filter can't match anything
+    // (eg. `a = 1 && a = 2` with [`optimize_scalar`]).
+    Never,
+}
+
+// optimize simplifies the expression groups into equivalent ones by:
+//   - dropping empty and needlessly nested groups (eg. `((a = 1))`)
+//   - resolving double negations (eg. `!(!(a = 1))`)
+//   - removing duplicated operands (eg. `a = 1 && a = 1`)
+//   - merging overlapping numeric ranges of the same identifier (eg. `a > 5 && a > 3`)
+//
+// The result is equivalent also for the multi-valued identifiers (eg. Elasticsearch
+// and MongoDB arrays, where `a > 5` matches if any of the elements is greater than 5)
+// and for case insensitive text comparisons (eg. MySQL collations), so the
+// contradicting comparisons are left as they are (see [`optimize_scalar`]).
+pub fn optimize<'a>(expr_groups: &ExprGroups<'a>) -> Optimized<'a> {
+    optimize_with(expr_groups, false)
+}
+
+// optimize_scalar simplifies the expression groups the same way as [`optimize`]
+// and additionally folds the contradicting comparisons of the same identifier
+// (eg. `a = 1 && a = 2` or `a > 5 && a < 3`).
+//
+// It assumes that each identifier holds a single value and that the text
+// comparisons are case sensitive, so it is not sound for multi-valued fields
+// (eg. `tags = 'a' && tags = 'b'` with the Elasticsearch and MongoDB arrays)
+// or case insensitive collations (eg. `a = 'x' && a = 'X'` with MySQL).
+//
+// Contradictions are detected only outside of negations because some backends
+// (eg. SQL) evaluate comparisons with null operands neither to true nor to false,
+// so `!(a = 1 && a = 2)` is not always a match.
+pub fn optimize_scalar<'a>(expr_groups: &ExprGroups<'a>) -> Optimized<'a> {
+    optimize_with(expr_groups, true)
+}
+
+fn optimize_with<'a>(expr_groups: &ExprGroups<'a>, scalar: bool) -> Optimized<'a> {
+    let optimizer = Optimizer { scalar };
+
+    match from_groups(expr_groups).map(|node| optimizer.simplify(node, false)) {
+        None | Some(Node::True) => Optimized::Always,
+        Some(Node::False) => Optimized::Never,
+        Some(Node::Or(children)) => Optimized::Groups(or_groups(children)),
+        Some(Node::And(children)) => Optimized::Groups(and_groups(children)),
+        Some(node) => Optimized::Groups(and_groups(vec![node])),
+    }
+}
+
+// Node represents a boolean expression with n-ary conjunctions and disjunctions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Node<'a> {
+    True,
+    False,
+    Expr(Expr<'a>),
+    Not(Box<Node<'a>>),
+    And(Vec<Node<'a>>),
+    Or(Vec<Node<'a>>),
+}
+
+// from_groups converts the expression groups into a disjunction of conjunctions
+// (aka. `&&` binds tighter than `||`).
+//
+// Returns `None` if there are no expressions to convert.
+fn from_groups<'a>(expr_groups: &ExprGroups<'a>) -> Option<Node<'a>> {
+    let mut disjunction = Vec::new();
+    let mut conjunction = Vec::new();
+
+    for group in expr_groups {
+        let node = match from_item(&group.item) {
+            Some(node) => node,
+            None => continue,
+        };
+
+        if group.join == JoinOp::Or && !conjunction.is_empty() {
+            disjunction.push(Node::And(std::mem::take(&mut conjunction)));
+        }
+
+        conjunction.push(node);
+    }
+
+    if !conjunction.is_empty() {
+        disjunction.push(Node::And(conjunction));
+    }
+
+    if disjunction.is_empty() {
+        None
+    } else {
+        Some(Node::Or(disjunction))
+    }
+}
+
+fn from_item<'a>(item: &ExprGroupItem<'a>) -> Option<Node<'a>> {
+    match item {
+        ExprGroupItem::Expr(expr) => Some(Node::Expr(expr.clone())),
+        ExprGroupItem::ExprGroups(expr_groups) => from_groups(expr_groups),
+        ExprGroupItem::Not(item) => from_item(item).map(|node| Node::Not(Box::new(node))),
+    }
+}
+
+// Optimizer simplifies the nodes of a single optimization pass.
+#[derive(Clone, Copy)]
+struct Optimizer {
+    // scalar enables the folding of the contradicting comparisons.
+    scalar: bool,
+}
+
+impl Optimizer {
+    // simplify returns the simplified node.
+    //
+    // `negated` indicates whether the node is under an odd number of negations.
+    fn simplify(self, node: Node, negated: bool) -> Node {
+        match node {
+            Node::Not(operand) => match self.simplify(*operand, !negated) {
+                Node::Not(operand) => *operand,
+                Node::True => Node::False,
+                Node::False => Node::True,
+                operand => Node::Not(Box::new(operand)),
+            },
+            Node::And(children) => self.simplify_and(children, negated),
+            Node::Or(children) => self.simplify_or(children, negated),
+            node => node,
+        }
+    }
+
+    fn simplify_and(self, children: Vec<Node>, negated: bool) -> Node {
+        let mut operands = Vec::with_capacity(children.len());
+
+        for child in children {
+            match self.simplify(child, negated) {
+                Node::And(nested) => operands.extend(nested),
+                Node::True => {}
+                Node::False => return Node::False,
+                child => operands.push(child),
+            }
+        }
+
+        dedup(&mut operands);
+
+        let (redundant, contradiction) = merge_ranges(&operands);
+        if contradiction && self.scalar && !negated {
+            return Node::False;
+        }
+
+        let mut redundant = redundant.into_iter();
+        operands.retain(|_| !redundant.next().unwrap_or_default());
+
+        match operands.len() {
+            0 => Node::True,
+            1 => operands.remove(0),
+            _ => Node::And(operands),
+        }
+    }
+
+    fn simplify_or(self, children: Vec<Node>, negated: bool) -> Node {
+        let mut operands = Vec::with_capacity(children.len());
+
+        for child in children {
+            match self.simplify(child, negated) {
+                Node::Or(nested) => operands.extend(nested),
+                Node::False => {}
+                Node::True => return Node::True,
+                child => operands.push(child),
+            }
+        }
+
+        dedup(&mut operands);
+
+        match operands.len() {
+            0 => Node::False,
+            1 => operands.remove(0),
+            _ => Node::Or(operands),
+        }
+    }
+}
+
+// dedup removes the repeated nodes, keeping their first occurrence.
+fn dedup(nodes: &mut Vec<Node>) {
+    let mut seen = HashSet::with_capacity(nodes.len());
+    nodes.retain(|node| seen.insert(node.clone()));
+}
+
+// Bounds represents the collected constraints of a single identifier
+// in a conjunction as `(operand index, value, inclusive)` tuples.
+#[derive(Default)]
+struct Bounds<'n> {
+    eq: Option<(usize, f64)>,
+    literal: Option<&'n Token<'n>>,
+    lower: Option<(usize, f64, bool)>,
+    upper: Option<(usize, f64, bool)>,
+}
+
+// merge_ranges checks the comparisons of the conjunction operands
+// and marks the ones that are implied by another operand as redundant.
+//
+// Returns the redundant operand flags and whether some of the operands
+// contradict each other (assuming scalar identifiers).
+fn merge_ranges(operands: &[Node]) -> (Vec<bool>, bool) {
+    let mut redundant = vec![false; operands.len()];
+    let mut contradiction = false;
+    let mut bounds: HashMap<&str, Bounds> = HashMap::new();
+
+    for (i, operand) in operands.iter().enumerate() {
+        let (name, op, literal) = match comparison(operand) {
+            Some(comparison) => comparison,
+            None => continue,
+        };
+
+        let entry = bounds.entry(name).or_default();
+
+        let value = match literal {
+            Token::Number(value) => match value.parse::<f64>() {
+                Ok(value) if !value.is_nan() => value,
+                _ => continue,
+            },
+            literal => {
+                if op == SignOp::Eq {
+                    match entry.literal {
+                        Some(prev) if same_kind(prev, literal) && prev != literal => {
+                            contradiction = true
+                        }
+                        Some(_) => {}
+                        None => entry.literal = Some(literal),
+                    }
+                }
+                continue;
+            }
+        };
+
+        match op {
+            SignOp::Eq => match entry.eq {
+                Some((_, prev)) if prev == value => redundant[i] = true,
+                Some(_) => contradiction = true,
+                None => entry.eq = Some((i, value)),
+            },
+            SignOp::Gt | SignOp::Gte => {
+                let bound = (i, value, op == SignOp::Gte);
+                match entry.lower {
+                    Some(prev) if !is_stricter(bound, prev, |a, b| a > b) => redundant[i] = true,
+                    Some((prev, _, _)) => {
+                        redundant[prev] = true;
+                        entry.lower = Some(bound);
+                    }
+                    None => entry.lower = Some(bound),
+                }
+            }
+            SignOp::Lt | SignOp::Lte => {
+                let bound = (i, value, op == SignOp::Lte);
+                match entry.upper {
+                    Some(prev) if !is_stricter(bound, prev, |a, b| a < b) => redundant[i] = true,
+                    Some((prev, _, _)) => {
+                        redundant[prev] = true;
+                        entry.upper = Some(bound);
+                    }
+                    None => entry.upper = Some(bound),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for entry in bounds.values() {
+        match entry.eq {
+            Some((_, value)) => {
+                // the equality implies the bounds that it satisfies
+                if let Some((i, lower, inclusive)) = entry.lower {
+                    if value > lower || (inclusive && value == lower) {
+                        redundant[i] = true;
+                    } else {
+                        contradiction = true;
+                    }
+                }
+
+                if let Some((i, upper, inclusive)) = entry.upper {
+                    if value < upper || (inclusive && value == upper) {
+                        redundant[i] = true;
+                    } else {
+                        contradiction = true;
+                    }
+                }
+            }
+            None => {
+                if let (Some((_, lower, lower_inclusive)), Some((_, upper, upper_inclusive))) =
+                    (entry.lower, entry.upper)
+                {
+                    if lower > upper || (lower == upper && !(lower_inclusive && upper_inclusive)) {
+                        contradiction = true;
+                    }
+                }
+            }
+        }
+    }
+
+    (redundant, contradiction)
+}
+
+// comparison returns the identifier, the operator and the literal operand
+// of an `identifier op literal` expression (or its flipped variant).
+fn comparison<'n>(node: &'n Node) -> Option<(&'n str, SignOp, &'n Token<'n>)> {
+    let expr = match node {
+        Node::Expr(expr) => expr,
+        _ => return None,
+    };
+
+    match (&expr.left, &expr.right) {
+        (Token::Identifier(name), literal) if is_literal(literal) => Some((name, expr.op, literal)),
+        (literal, Token::Identifier(name)) if is_literal(literal) => {
+            Some((name, flip(expr.op), literal))
+        }
+        _ => None,
+    }
+}
+
+fn is_literal(token: &Token) -> bool {
+    matches!(token, Token::Number(_) | Token::Text(_) | Token::Bool(_))
+}
+
+fn same_kind(a: &Token, b: &Token) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+// is_stricter checks if the bound is stricter than the previous one,
+// where `beyond` reports whether a value is past another one.
+fn is_stricter(
+    (_, value, inclusive): (usize, f64, bool),
+    (_, prev, prev_inclusive): (usize, f64, bool),
+    beyond: impl Fn(f64, f64) -> bool,
+) -> bool {
+    beyond(value, prev) || (value == prev && !inclusive && prev_inclusive)
+}
+
+// and_groups converts the conjunction operands back into expression groups.
+fn and_groups<'a>(operands: Vec<Node<'a>>) -> ExprGroups<'a> {
+    operands
+        .into_iter()
+        .map(|operand| ExprGroup::new(JoinOp::And, into_item(operand)))
+        .collect()
+}
+
+// or_groups converts the disjunction operands back into expression groups,
+// inlining the nested conjunctions (they bind tighter anyway).
+fn or_groups<'a>(operands: Vec<Node<'a>>) -> ExprGroups<'a> {
+    let mut expr_groups = ExprGroups::new();
+
+    for operand in operands {
+        let join = if expr_groups.is_empty() {
+            JoinOp::And
+        } else {
+            JoinOp::Or
+        };
+
+        match operand {
+            Node::And(nested) => {
+                for (i, item) in nested.into_iter().enumerate() {
+                    let join = if i == 0 { join } else { JoinOp::And };
+                    expr_groups.push(ExprGroup::new(join, into_item(item)));
+                }
+            }
+            operand => expr_groups.push(ExprGroup::new(join, into_item(operand))),
+        }
+    }
+
+    expr_groups
+}
+
+fn into_item(node: Node) -> ExprGroupItem {
+    match node {
+        Node::Expr(expr) => ExprGroupItem::Expr(expr),
+        Node::Not(operand) => ExprGroupItem::Not(Box::new(into_item(*operand))),
+        Node::And(operands) => ExprGroupItem::ExprGroups(and_groups(operands)),
+        Node::Or(operands) => ExprGroupItem::ExprGroups(or_groups(operands)),
+        // constants are folded before the conversion
+        Node::True | Node::False => unreachable!("unexpected constant node"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        format::format,
+        optimize::{optimize, optimize_scalar, Optimized},
+        parser::{parse, ExprGroup, ExprGroupItem, ExprGroups},
+        scanner::JoinOp,
+    };
+
+    #[test]
+    fn test_optimize() {
+        struct Scenario {
+            input: &'static str,
+            expected: &'static str,
+            scalar: &'static str,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: "((a = 1))",
+                expected: "a = 1",
+                scalar: "a = 1",
+            },
+            Scenario {
+                input: "a = 1 && a = 1 && b = 2",
+                expected: "a = 1 && b = 2",
+                scalar: "a = 1 && b = 2",
+            },
+            Scenario {
+                input: "a = 1 || (b = 2 && c = 3) || a = 1",
+                expected: "a = 1 || b = 2 && c = 3",
+                scalar: "a = 1 || b = 2 && c = 3",
+            },
+            Scenario {
+                input: "a = 1 && (b = 2 && (c = 3 || d = 4))",
+                expected: "a = 1 && b = 2 && (c = 3 || d = 4)",
+                scalar: "a = 1 && b = 2 && (c = 3 || d = 4)",
+            },
+            Scenario {
+                input: "(a = 1 || b = 2) || (c = 3 || b = 2)",
+                expected: "a = 1 || b = 2 || c = 3",
+                scalar: "a = 1 || b = 2 || c = 3",
+            },
+            Scenario {
+                input: "!(!(a = 1)) && !(!(!(b = 2)))",
+                expected: "a = 1 && !b = 2",
+                scalar: "a = 1 && !b = 2",
+            },
+            Scenario {
+                input: "x > 5 && x > 3 && x >= 5",
+                expected: "x > 5",
+                scalar: "x > 5",
+            },
+            Scenario {
+                input: "x < 10 && y > 1 && 3 < x && x <= 10 && x > 2",
+                expected: "x < 10 && y > 1 && 3 < x",
+                scalar: "x < 10 && y > 1 && 3 < x",
+            },
+            Scenario {
+                input: "x = 5 && x > 3 && x <= 5 && x != 4",
+                expected: "x = 5 && x != 4",
+                scalar: "x = 5 && x != 4",
+            },
+            Scenario {
+                input: "x = 1.0 && x = 1",
+                expected: "x = 1.0",
+                scalar: "x = 1.0",
+            },
+            Scenario {
+                input: "x >= 1 && x <= 1",
+                expected: "x >= 1 && x <= 1",
+                scalar: "x >= 1 && x <= 1",
+            },
+            Scenario {
+                input: "a = 1 && a = 2",
+                expected: "a = 1 && a = 2",
+                scalar: "<never>",
+            },
+            Scenario {
+                input: "a = 'x' && b = 1 && a = 'y'",
+                expected: "a = 'x' && b = 1 && a = 'y'",
+                scalar: "<never>",
+            },
+            Scenario {
+                input: "a = 'x' && a = 1 && a = true",
+                expected: "a = 'x' && a = 1 && a = true",
+                scalar: "a = 'x' && a = 1 && a = true",
+            },
+            Scenario {
+                input: "x > 5 && x < 3",
+                expected: "x > 5 && x < 3",
+                scalar: "<never>",
+            },
+            Scenario {
+                input: "x > 5 && x <= 5",
+                expected: "x > 5 && x <= 5",
+                scalar: "<never>",
+            },
+            Scenario {
+                input: "x = 1 && x > 1",
+                expected: "x = 1 && x > 1",
+                scalar: "<never>",
+            },
+            Scenario {
+                input: "(a = 1 && a = 2) || b = 3",
+                expected: "a = 1 && a = 2 || b = 3",
+                scalar: "b = 3",
+            },
+            Scenario {
+                input: "(a = 1 && a = 2) || (x > 1 && x < 0)",
+                expected: "a = 1 && a = 2 || x > 1 && x < 0",
+                scalar: "<never>",
+            },
+            Scenario {
+                input: "!(a = 1 && a = 2 && a = 2)",
+                expected: "!(a = 1 && a = 2)",
+                scalar: "!(a = 1 && a = 2)",
+            },
+            Scenario {
+                input: "!(!(a = 1 && a = 2)) || b = 1",
+                expected: "a = 1 && a = 2 || b = 1",
+                scalar: "b = 1",
+            },
+            Scenario {
+                input: "a ?= 1 && a ?= 2 && a = b && b = 1",
+                expected: "a ?= 1 && a ?= 2 && a = b && b = 1",
+                scalar: "a ?= 1 && a ?= 2 && a = b && b = 1",
+            },
+            Scenario {
+                input: "tags = 'a' && tags = 'b' || a = 'x' && a = 'X'",
+                expected: "tags = 'a' && tags = 'b' || a = 'x' && a = 'X'",
+                scalar: "<never>",
+            },
+            Scenario {
+                input: "x = 1 && x = 2 && x > 0 && y > 5 && y > 3",
+                expected: "x = 1 && x = 2 && y > 5",
+                scalar: "<never>",
+            },
+        ];
+
+        fn to_string(optimized: Optimized) -> String {
+            match optimized {
+                Optimized::Groups(expr_groups) => format(&expr_groups).unwrap(),
+                Optimized::Always => "<always>".to_string(),
+                Optimized::Never => "<never>".to_string(),
+            }
+        }
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let expr_groups = parse(scenario.input).unwrap();
+
+            let result = to_string(optimize(&expr_groups));
+            assert!(
+                result == scenario.expected,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected,
+                result
+            );
+
+            let result = to_string(optimize_scalar(&expr_groups));
+            assert!(
+                result == scenario.scalar,
+                "({}) Expected scalar {}, got {}",
+                i,
+                scenario.scalar,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_optimize_empty_groups() {
+        let empty = ExprGroups::from(vec![ExprGroup::new(JoinOp::And, ExprGroups::new())]);
+
+        let result = optimize(&ExprGroups::new());
+        assert!(
+            result == Optimized::Always,
+            "Expected Always for no groups, got {:?}",
+            result
+        );
+
+        let result = optimize(&ExprGroups::from(vec![
+            ExprGroup::new(JoinOp::And, empty.clone()),
+            ExprGroup::new(
+                JoinOp::Or,
+                ExprGroupItem::Not(Box::new(empty.clone().into())),
+            ),
+        ]));
+        assert!(
+            result == Optimized::Always,
+            "Expected Always for empty nested groups, got {:?}",
+            result
+        );
+
+        let mut expr_groups = parse("a = 1").unwrap();
+        expr_groups.push(ExprGroup::new(JoinOp::Or, empty));
+        let expected = Optimized::Groups(parse("a = 1").unwrap());
+        let result = optimize(&expr_groups);
+        assert!(
+            result == expected,
+            "Expected {:?}, got {:?}",
+            expected,
+            result
+        );
+    }
+}
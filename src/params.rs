@@ -0,0 +1,170 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::{
+    error::{Error, ErrorKind},
+    scanner::Token,
+    span::Span,
+};
+
+// Param represents a single placeholder parameter value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Param {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    // List is a list of (non-list) values that could be used only with the `in` and `!in` operators.
+    List(Vec<Param>),
+}
+
+impl Param {
+    // token converts the parameter into a literal token.
+    fn token(&self, placeholder: &Token, span: Span) -> Result<Token<'static>, Error> {
+        match self {
+            Param::Null => Ok(Token::Null(Cow::Borrowed("null"))),
+            Param::Bool(value) => Ok(Token::Bool(Cow::Borrowed(if *value {
+                "true"
+            } else {
+                "false"
+            }))),
+            Param::Int(value) => Ok(Token::Number(Cow::Owned(value.to_string()))),
+            Param::Float(value) if value.is_finite() => {
+                Ok(Token::Number(Cow::Owned(value.to_string())))
+            }
+            Param::Float(_) => Err(Error::new(
+                ErrorKind::InvalidNumber,
+                placeholder.clone(),
+                span,
+            )),
+            Param::Text(value) => Ok(Token::Text(Cow::Owned(value.clone()))),
+            Param::List(items) => items
+                .iter()
+                .map(|item| match item {
+                    Param::List(_) => Err(Error::new(
+                        ErrorKind::InvalidList,
+                        placeholder.clone(),
+                        span,
+                    )),
+                    item => item.token(placeholder, span),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Token::List),
+        }
+    }
+}
+
+impl From<bool> for Param {
+    fn from(value: bool) -> Self {
+        Param::Bool(value)
+    }
+}
+
+impl From<i32> for Param {
+    fn from(value: i32) -> Self {
+        Param::Int(i64::from(value))
+    }
+}
+
+impl From<i64> for Param {
+    fn from(value: i64) -> Self {
+        Param::Int(value)
+    }
+}
+
+impl From<u32> for Param {
+    fn from(value: u32) -> Self {
+        Param::Int(i64::from(value))
+    }
+}
+
+impl From<f64> for Param {
+    fn from(value: f64) -> Self {
+        Param::Float(value)
+    }
+}
+
+impl From<&str> for Param {
+    fn from(value: &str) -> Self {
+        Param::Text(value.to_string())
+    }
+}
+
+impl From<String> for Param {
+    fn from(value: String) -> Self {
+        Param::Text(value)
+    }
+}
+
+impl<T: Into<Param>> From<Option<T>> for Param {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Param::Null, Into::into)
+    }
+}
+
+impl<T: Into<Param>> From<Vec<T>> for Param {
+    fn from(value: Vec<T>) -> Self {
+        Param::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+// Params represents the named (aka. `{:name}`) and positional (aka. `?`)
+// placeholder parameters.
+//
+// The positional parameters are bound in the order of the `?` placeholders in the text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params {
+    named: HashMap<String, Param>,
+    positional: Vec<Param>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // with_named returns the params with the `{:name}` placeholder value.
+    pub fn with_named(mut self, name: &str, value: impl Into<Param>) -> Self {
+        self.named.insert(name.to_string(), value.into());
+        self
+    }
+
+    // with_positional returns the params with the next `?` placeholder value.
+    pub fn with_positional(mut self, value: impl Into<Param>) -> Self {
+        self.positional.push(value.into());
+        self
+    }
+
+    // bind returns the literal token of the placeholder.
+    //
+    // `position` is the index of the next positional parameter
+    // and it is incremented for each bound `?` placeholder.
+    pub(crate) fn bind(
+        &self,
+        placeholder: &Token,
+        span: Span,
+        position: &mut usize,
+    ) -> Result<Token<'static>, Error> {
+        let literal = placeholder.literal();
+
+        let param = match literal
+            .strip_prefix("{:")
+            .and_then(|name| name.strip_suffix('}'))
+        {
+            Some(name) => self.named.get(name),
+            None => {
+                *position += 1;
+                self.positional.get(*position - 1)
+            }
+        };
+
+        match param {
+            Some(param) => param.token(placeholder, span),
+            None => Err(Error::new(
+                ErrorKind::MissingParam,
+                placeholder.clone(),
+                span,
+            )),
+        }
+    }
+}
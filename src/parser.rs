@@ -1,84 +1,292 @@
-use std::io::BufReader;
-
 use crate::{
-    error::Error,
+    error::{Error, ErrorKind},
+    params::Params,
     scanner::{JoinOp, Scanner, SignOp, Token},
+    span::{Position, Span},
 };
 
 // Expr represents an individual tokenized expression consisting
 // of left operand, operator and a right operand.
-#[derive(Default, Clone)]
-pub struct Expr {
-    pub left: Token,
+//
+// The span covers the expression from the start of its left operand
+// to the end of its right operand. It is only informative and it is
+// ignored when comparing or hashing expressions, so that a parsed
+// expression is equal to the same one constructed in code.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Expr<'a> {
+    pub left: Token<'a>,
     pub op: SignOp,
-    pub right: Token,
+    pub right: Token<'a>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub span: Span,
 }
 
-impl std::fmt::Display for Expr {
+impl std::fmt::Display for Expr<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{{{} {} {}}}", self.left, self.op, self.right)
     }
 }
 
-impl Expr {
+impl PartialEq for Expr<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.left == other.left && self.op == other.op && self.right == other.right
+    }
+}
+
+impl Eq for Expr<'_> {}
+
+impl std::hash::Hash for Expr<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.left.hash(state);
+        self.op.hash(state);
+        self.right.hash(state);
+    }
+}
+
+impl<'a> Expr<'a> {
+    // new creates a new expression from the provided operands and operator
+    // (with an empty span).
+    pub fn new(left: Token<'a>, op: SignOp, right: Token<'a>) -> Self {
+        Self {
+            left,
+            op,
+            right,
+            span: Span::default(),
+        }
+    }
+
     fn is_zero(&self) -> bool {
         self.op == SignOp::None && self.left == Token::None && self.right == Token::None
     }
+
+    // into_owned converts the expression into one that doesn't borrow from the parsed text.
+    pub fn into_owned(self) -> Expr<'static> {
+        Expr {
+            left: self.left.into_owned(),
+            op: self.op,
+            right: self.right.into_owned(),
+            span: self.span,
+        }
+    }
 }
 
 // ExprGroup represents a wrapped expression and its join type.
 //
 // The group's Item could be either an `Expr` instance or `ExprGroups` slice (for nested expressions).
-pub struct ExprGroup {
+// For nested expressions the span includes the wrapping parenthesis.
+// Similar to `Expr`, the span is ignored when comparing or hashing groups.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExprGroup<'a> {
     pub join: JoinOp,
-    pub item: ExprGroupItem,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub item: ExprGroupItem<'a>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub span: Span,
 }
 
-impl std::fmt::Display for ExprGroup {
+impl PartialEq for ExprGroup<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.join == other.join && self.item == other.item
+    }
+}
+
+impl Eq for ExprGroup<'_> {}
+
+impl std::hash::Hash for ExprGroup<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.join.hash(state);
+        self.item.hash(state);
+    }
+}
+
+impl<'a> ExprGroup<'a> {
+    // new creates a new group from the provided join operator and item
+    // (with an empty span).
+    pub fn new(join: JoinOp, item: impl Into<ExprGroupItem<'a>>) -> Self {
+        Self {
+            join,
+            item: item.into(),
+            span: Span::default(),
+        }
+    }
+
+    // into_owned converts the group into one that doesn't borrow from the parsed text.
+    pub fn into_owned(self) -> ExprGroup<'static> {
+        ExprGroup {
+            join: self.join,
+            item: self.item.into_owned(),
+            span: self.span,
+        }
+    }
+}
+
+impl std::fmt::Display for ExprGroup<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{{{} {}}}", self.join, self.item)
     }
 }
 
-pub enum ExprGroupItem {
-    Expr(Expr),
-    ExprGroups(ExprGroups),
+// ExprGroupItem represents the content of a single group -
+// an expression, nested groups or a negation of either.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExprGroupItem<'a> {
+    Expr(Expr<'a>),
+    ExprGroups(ExprGroups<'a>),
+    // negated expression or nested group (eg. `!a=1` or `!(a=1 || b=2)`)
+    Not(Box<ExprGroupItem<'a>>),
 }
 
-impl std::fmt::Display for ExprGroupItem {
+impl ExprGroupItem<'_> {
+    // into_owned converts the item into one that doesn't borrow from the parsed text.
+    pub fn into_owned(self) -> ExprGroupItem<'static> {
+        match self {
+            ExprGroupItem::Expr(expr) => ExprGroupItem::Expr(expr.into_owned()),
+            ExprGroupItem::ExprGroups(expr_groups) => {
+                ExprGroupItem::ExprGroups(expr_groups.into_owned())
+            }
+            ExprGroupItem::Not(item) => ExprGroupItem::Not(Box::new(item.into_owned())),
+        }
+    }
+}
+
+impl<'a> From<Expr<'a>> for ExprGroupItem<'a> {
+    fn from(expr: Expr<'a>) -> Self {
+        ExprGroupItem::Expr(expr)
+    }
+}
+
+impl<'a> From<ExprGroups<'a>> for ExprGroupItem<'a> {
+    fn from(expr_groups: ExprGroups<'a>) -> Self {
+        ExprGroupItem::ExprGroups(expr_groups)
+    }
+}
+
+impl std::fmt::Display for ExprGroupItem<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ExprGroupItem::Expr(expr) => write!(f, "{expr}",),
             ExprGroupItem::ExprGroups(expr_groups) => write!(f, "{expr_groups}",),
+            ExprGroupItem::Not(item) => write!(f, "!{item}",),
         }
     }
 }
 
-pub struct ExprGroups {
-    expr_groups: Vec<ExprGroup>,
+// ExprGroups represents an ordered list of expression groups
+// (aka. the parsed filter or the content of a parenthesis).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ExprGroups<'a> {
+    expr_groups: Vec<ExprGroup<'a>>,
 }
 
-impl ExprGroups {
-    fn new() -> Self {
+impl<'a> ExprGroups<'a> {
+    pub fn new() -> Self {
         Self {
             expr_groups: Vec::new(),
         }
     }
 
-    pub fn get(&self) -> &Vec<ExprGroup> {
+    pub fn get(&self) -> &Vec<ExprGroup<'a>> {
         &self.expr_groups
     }
 
-    fn push(&mut self, value: ExprGroup) {
+    // iter returns an iterator over the groups.
+    pub fn iter(&self) -> std::slice::Iter<'_, ExprGroup<'a>> {
+        self.expr_groups.iter()
+    }
+
+    // iter_mut returns an iterator that allows modifying the groups.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, ExprGroup<'a>> {
+        self.expr_groups.iter_mut()
+    }
+
+    // push appends a group to the end of the list.
+    pub fn push(&mut self, value: ExprGroup<'a>) {
         self.expr_groups.push(value)
     }
 
-    fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.expr_groups.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.expr_groups.is_empty()
+    }
+
+    // into_owned converts the groups into ones that don't borrow from the parsed text.
+    pub fn into_owned(self) -> ExprGroups<'static> {
+        ExprGroups {
+            expr_groups: self
+                .expr_groups
+                .into_iter()
+                .map(ExprGroup::into_owned)
+                .collect(),
+        }
+    }
+}
+
+impl<'a> From<Vec<ExprGroup<'a>>> for ExprGroups<'a> {
+    fn from(expr_groups: Vec<ExprGroup<'a>>) -> Self {
+        Self { expr_groups }
+    }
+}
+
+impl<'a> FromIterator<ExprGroup<'a>> for ExprGroups<'a> {
+    fn from_iter<T: IntoIterator<Item = ExprGroup<'a>>>(iter: T) -> Self {
+        Self {
+            expr_groups: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for ExprGroups<'a> {
+    type Item = ExprGroup<'a>;
+    type IntoIter = std::vec::IntoIter<ExprGroup<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.expr_groups.into_iter()
+    }
 }
 
-impl std::fmt::Display for ExprGroups {
+impl<'a, 'b> IntoIterator for &'b ExprGroups<'a> {
+    type Item = &'b ExprGroup<'a>;
+    type IntoIter = std::slice::Iter<'b, ExprGroup<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.expr_groups.iter()
+    }
+}
+
+impl<'a, 'b> IntoIterator for &'b mut ExprGroups<'a> {
+    type Item = &'b mut ExprGroup<'a>;
+    type IntoIter = std::slice::IterMut<'b, ExprGroup<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.expr_groups.iter_mut()
+    }
+}
+
+impl<'a> std::ops::Index<usize> for ExprGroups<'a> {
+    type Output = ExprGroup<'a>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.expr_groups[index]
+    }
+}
+
+impl std::ops::IndexMut<usize> for ExprGroups<'_> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.expr_groups[index]
+    }
+}
+
+impl std::fmt::Display for ExprGroups<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
         for (i, expr_group) in self.expr_groups.iter().enumerate() {
@@ -98,163 +306,455 @@ enum Step {
     BeforeSign,
     Sign,
     AfterSign,
+    AfterListSign,
     Join,
 }
 
+impl Step {
+    // expected returns the token kinds accepted by the step.
+    fn expected(&self) -> &'static [&'static str] {
+        match self {
+            Step::BeforeSign => &[
+                "identifier",
+                "text",
+                "number",
+                "bool",
+                "null",
+                "placeholder",
+                "group_start",
+                "not",
+            ],
+            Step::Sign => &["sign"],
+            Step::AfterSign => &[
+                "identifier",
+                "text",
+                "number",
+                "bool",
+                "null",
+                "placeholder",
+            ],
+            Step::AfterListSign => &["list", "placeholder"],
+            Step::Join => &["join"],
+        }
+    }
+}
+
 // Parse parses the provided text and returns its processed AST
 // in the form of `ExprGroup` slice(s).
 //
 // Comments and whitespaces are ignored.
-pub fn parse(text: &str) -> Result<ExprGroups, Error> {
-    let mut result = ExprGroups::new();
-    let mut scanner = Scanner::new(BufReader::new(text.as_bytes()))?;
-    let mut step = Step::BeforeSign;
-    let mut join = JoinOp::And;
+//
+// The returned AST borrows its literals from the provided text
+// (use `ExprGroups::into_owned` to detach it).
+//
+// Placeholders are not allowed (use `parse_with_params` instead).
+pub fn parse(text: &str) -> Result<ExprGroups<'_>, Error> {
+    parse_with_params(text, &Params::new())
+}
 
-    let mut expr = Expr::default();
+// parse_with_params parses the provided text and replaces its named
+// (aka. `{:name}`) and positional (aka. `?`) placeholders with the params.
+//
+// The params are bound as literal tokens (they are never scanned) so their
+// values can't change the structure of the expression.
+// Placeholders are allowed only as operands (list params only after `in` and `!in`).
+pub fn parse_with_params<'a>(text: &'a str, params: &Params) -> Result<ExprGroups<'a>, Error> {
+    parse_with(text, params, &ParseOptions::default())
+}
 
-    loop {
-        let t = scanner.scan()?;
+// ParseOptions represents the parse params and the resource limits
+// that should be enforced when parsing untrusted input.
+//
+// A `None` limit is not enforced. By default only the nesting depth
+// is limited (to `DEFAULT_MAX_DEPTH`), so that even `parse` and
+// `parse_with_params` can't exhaust the stack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    // max_len is the max length of the input text in bytes.
+    pub max_len: Option<usize>,
+    // max_depth is the max nesting level of the parenthesized groups
+    // and negations (each `!` counts as a level).
+    pub max_depth: Option<usize>,
+    // max_exprs is the max number of expressions (including the nested ones).
+    pub max_exprs: Option<usize>,
+    // max_text_len is the max length of a single text literal in bytes
+    // (including the list items and the bound params).
+    pub max_text_len: Option<usize>,
+    // params are the placeholder params (see `parse_with_params`).
+    pub params: Params,
+}
+
+// DEFAULT_MAX_DEPTH is the default `ParseOptions::max_depth` limit.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
 
-        if matches!(t, Token::Eof(_)) {
-            break;
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_len: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_exprs: None,
+            max_text_len: None,
+            params: Params::default(),
         }
+    }
+}
+
+// parse_with_options parses the provided text similar to `parse_with_params`
+// but fails as soon as any of the options limits is exceeded.
+//
+// The input length is checked before the text is scanned at all and the nesting
+// depth is checked before descending into a group or negation, so a malicious input
+// (eg. `((((...))))` or `!!!!...`) can't exhaust the stack.
+pub fn parse_with_options<'a>(
+    text: &'a str,
+    options: &ParseOptions,
+) -> Result<ExprGroups<'a>, Error> {
+    parse_with(text, &options.params, options)
+}
+
+fn parse_with<'a>(
+    text: &'a str,
+    params: &Params,
+    options: &ParseOptions,
+) -> Result<ExprGroups<'a>, Error> {
+    if matches!(options.max_len, Some(max) if text.len() > max) {
+        let start = Position {
+            offset: 0,
+            line: 1,
+            column: 1,
+        };
+        return Err(Error::new(
+            ErrorKind::TooLong,
+            Token::None,
+            Span::new(start, start),
+        ));
+    }
 
-        if matches!(t, Token::Ws(_)) || matches!(t, Token::Comment(_)) {
-            continue;
+    let mut parser = Parser {
+        scanner: Scanner::new(text),
+        params,
+        options,
+        position: 0,
+        depth: 0,
+        exprs: 0,
+        pending: None,
+    };
+
+    parser.parse_groups(None).map(|(result, _)| result)
+}
+
+// Parser represents a single pass recursive descent parser
+// consuming the scanner tokens.
+struct Parser<'a, 'p> {
+    scanner: Scanner<'a>,
+    params: &'p Params,
+    options: &'p ParseOptions,
+    // index of the next positional parameter
+    position: usize,
+    // nesting level of the currently parsed groups
+    depth: usize,
+    // number of the parsed expressions
+    exprs: usize,
+    // token that should be returned by the next call of `next`
+    pending: Option<(Token<'a>, Span)>,
+}
+
+impl<'a> Parser<'a, '_> {
+    // next returns the next token that is not a whitespace or a comment.
+    fn next(&mut self) -> Result<(Token<'a>, Span), Error> {
+        if let Some(pending) = self.pending.take() {
+            return Ok(pending);
         }
 
-        if matches!(t, Token::Group(_)) {
-            let group_result = parse(t.literal())?;
+        loop {
+            let (t, span) = self.scanner.scan_spanned()?;
 
-            // append only if non-empty group
-            if group_result.len() > 0 {
-                result.push(ExprGroup {
-                    join,
-                    item: ExprGroupItem::ExprGroups(group_result),
-                })
+            if !matches!(t, Token::Ws(_)) && !matches!(t, Token::Comment(_)) {
+                return Ok((t, span));
             }
+        }
+    }
 
-            step = Step::Join;
-            continue;
+    // bind replaces a placeholder token with its parameter value
+    // and checks the text literals length.
+    fn bind(&mut self, t: Token<'a>, span: Span) -> Result<Token<'a>, Error> {
+        let t = match t {
+            Token::Placeholder(_) => self.params.bind(&t, span, &mut self.position)?,
+            t => t,
+        };
+
+        if let Some(max) = self.options.max_text_len {
+            let text = match &t {
+                Token::List(items) => items
+                    .iter()
+                    .find(|item| matches!(item, Token::Text(value) if value.len() > max)),
+                Token::Text(value) if value.len() > max => Some(&t),
+                _ => None,
+            };
+
+            if let Some(text) = text {
+                return Err(Error::new(ErrorKind::TextTooLong, text.clone(), span));
+            }
         }
 
-        match step {
-            Step::BeforeSign => {
-                if !matches!(t, Token::Identifier(_))
-                    && !matches!(t, Token::Text(_))
-                    && !matches!(t, Token::Number(_))
-                {
-                    return Err(Error::Unexpected(format!(
-                        "Expected left operand (identifier, text or number), got {} ({})",
-                        t.literal(),
-                        t.kind()
-                    )));
-                }
+        Ok(t)
+    }
 
-                expr = Expr {
-                    left: t,
-                    ..Default::default()
-                };
+    // parse_groups parses all expressions until EOF or, for nested groups
+    // (aka. when the opening parenthesis span is provided), until the matching
+    // closing parenthesis.
+    //
+    // Returns the parsed groups and the span of their terminating token.
+    fn parse_groups(&mut self, group_start: Option<Span>) -> Result<(ExprGroups<'a>, Span), Error> {
+        let mut result = ExprGroups::new();
+        let mut step = Step::BeforeSign;
+        let mut join = JoinOp::And;
+
+        let mut expr = Expr::default();
+
+        // number of the pending negation prefixes and the span of the first one
+        let mut negations = 0;
+        let mut negation_span = Span::default();
+
+        let (end, end_span) = loop {
+            let (t, span) = self.next()?;
 
-                step = Step::Sign
+            if matches!(t, Token::Eof(_)) {
+                break (t, span);
             }
-            Step::Sign => {
-                if !matches!(t, Token::Sign(_)) {
-                    return Err(Error::Unexpected(format!(
-                        "Expected a sign operator, got {} ({})",
-                        t.literal(),
-                        t.kind()
-                    )));
+
+            if matches!(t, Token::GroupEnd(_)) {
+                // unmatched closing parenthesis
+                if group_start.is_none() {
+                    return Err(unexpected(t, span, &step));
                 }
 
-                expr.op = match SignOp::from_str(t.literal()) {
-                    Some(op) => op,
-                    None => {
-                        return Err(Error::Unexpected(format!(
-                            "Expected a sign operator, got {} ({})",
-                            t.literal(),
-                            t.kind()
-                        )))
+                break (t, span);
+            }
+
+            match step {
+                Step::BeforeSign => {
+                    // the `in` and `!in` keywords are sign operators only after the left
+                    // operand, otherwise they refer to an `in` identifier (eg. `!in = 1`)
+                    let (t, span) = match t {
+                        Token::Sign(literal) if literal == "in" => {
+                            (Token::Identifier(literal), span)
+                        }
+                        Token::Sign(literal) if literal == "!in" => {
+                            let middle = Position {
+                                offset: span.start.offset + 1,
+                                column: span.start.column + 1,
+                                ..span.start
+                            };
+                            self.pending =
+                                Some((Token::Identifier("in".into()), Span::new(middle, span.end)));
+                            (Token::Not("!".into()), Span::new(span.start, middle))
+                        }
+                        t => (t, span),
+                    };
+
+                    if matches!(t, Token::Not(_)) {
+                        // each negation counts as a nesting level too
+                        if matches!(self.options.max_depth, Some(max) if self.depth + negations >= max)
+                        {
+                            return Err(Error::new(ErrorKind::TooDeep, t, span));
+                        }
+
+                        if negations == 0 {
+                            negation_span = span;
+                        }
+                        negations += 1;
+                        continue;
                     }
-                };
 
-                step = Step::AfterSign;
-            }
-            Step::AfterSign => {
-                if !matches!(t, Token::Identifier(_))
-                    && !matches!(t, Token::Text(_))
-                    && !matches!(t, Token::Number(_))
-                {
-                    return Err(Error::Unexpected(format!(
-                        "Expected right operand (identifier, text or number), got {} ({})",
-                        t.literal(),
-                        t.kind(),
-                    )));
+                    if matches!(t, Token::GroupStart(_)) {
+                        if matches!(self.options.max_depth, Some(max) if self.depth + negations >= max)
+                        {
+                            return Err(Error::new(ErrorKind::TooDeep, t, span));
+                        }
+
+                        self.depth += negations + 1;
+                        let (group_result, group_end) = self.parse_groups(Some(span))?;
+                        self.depth -= negations + 1;
+
+                        let item = ExprGroupItem::ExprGroups(group_result);
+                        let mut span = span.to(group_end);
+                        if negations > 0 {
+                            span = negation_span.to(span);
+                        }
+
+                        result.push(ExprGroup {
+                            join,
+                            item: negate(item, negations),
+                            span,
+                        });
+
+                        negations = 0;
+                        step = Step::Join;
+                        continue;
+                    }
+
+                    let t = self.bind(t, span)?;
+                    if !is_operand(&t) {
+                        return Err(unexpected(t, span, &step));
+                    }
+
+                    expr = Expr {
+                        left: t,
+                        span,
+                        ..Default::default()
+                    };
+
+                    step = Step::Sign
                 }
+                Step::Sign => {
+                    if !matches!(t, Token::Sign(_)) {
+                        return Err(unexpected(t, span, &step));
+                    }
 
-                expr.right = t;
-                result.push(ExprGroup {
-                    join,
-                    item: ExprGroupItem::Expr(expr.clone()),
-                });
+                    expr.op = match SignOp::from_str(t.literal()) {
+                        Some(op) => op,
+                        None => return Err(unexpected(t, span, &step)),
+                    };
 
-                step = Step::Join;
-            }
-            Step::Join => {
-                if !matches!(t, Token::Join(_)) {
-                    return Err(Error::Unexpected(format!(
-                        "Expected && or ||, got {} ({})",
-                        t.literal(),
-                        t.kind()
-                    )));
+                    // membership operators accept only a list literal
+                    step = match expr.op {
+                        SignOp::In | SignOp::Nin => Step::AfterListSign,
+                        _ => Step::AfterSign,
+                    };
                 }
+                Step::AfterSign | Step::AfterListSign => {
+                    let t = match t {
+                        Token::Sign(literal) if literal == "in" && step == Step::AfterSign => {
+                            Token::Identifier(literal)
+                        }
+                        t => t,
+                    };
+                    let t = self.bind(t, span)?;
+                    let valid = match step {
+                        Step::AfterListSign => matches!(t, Token::List(_)),
+                        _ => is_operand(&t),
+                    };
+                    if !valid {
+                        return Err(unexpected(t, span, &step));
+                    }
+
+                    expr.right = t;
+                    expr.span = expr.span.to(span);
 
-                join = match JoinOp::from_str(t.literal()) {
-                    Some(join) => join,
-                    None => {
-                        return Err(Error::Unexpected(format!(
-                            "Expected && or ||, got {} ({})",
-                            t.literal(),
-                            t.kind()
-                        )))
+                    self.exprs += 1;
+                    if matches!(self.options.max_exprs, Some(max) if self.exprs > max) {
+                        return Err(Error::new(ErrorKind::TooManyExprs, Token::None, expr.span));
                     }
-                };
 
-                step = Step::BeforeSign;
+                    let mut span = expr.span;
+                    if negations > 0 {
+                        span = negation_span.to(span);
+                    }
+
+                    let item = ExprGroupItem::Expr(std::mem::take(&mut expr));
+                    result.push(ExprGroup {
+                        join,
+                        item: negate(item, negations),
+                        span,
+                    });
+
+                    negations = 0;
+                    step = Step::Join;
+                }
+                Step::Join => {
+                    if !matches!(t, Token::Join(_)) {
+                        return Err(unexpected(t, span, &step));
+                    }
+
+                    join = match JoinOp::from_str(t.literal()) {
+                        Some(join) => join,
+                        None => return Err(unexpected(t, span, &step)),
+                    };
+
+                    step = Step::BeforeSign;
+                }
+            }
+        };
+
+        if step != Step::Join {
+            if result.is_empty() && expr.is_zero() && negations == 0 {
+                return Err(
+                    Error::new(ErrorKind::Empty, end, end_span).with_expected(step.expected())
+                );
+            }
+
+            if matches!(end, Token::GroupEnd(_)) {
+                return Err(unexpected(end, end_span, &step));
             }
+
+            return Err(
+                Error::new(ErrorKind::Incomplete, end, end_span).with_expected(step.expected())
+            );
         }
-    }
 
-    if step != Step::Join {
-        if result.len() == 0 && expr.is_zero() {
-            return Err(Error::Empty("Empty filter expression".to_owned()));
+        // missing closing parenthesis
+        if let (Some(group_start), Token::Eof(_)) = (group_start, &end) {
+            return Err(Error::new(
+                ErrorKind::InvalidGroup,
+                Token::GroupStart("(".into()),
+                group_start,
+            )
+            .with_expected(&["join", "group_end"]));
         }
 
-        return Err(Error::Incomplete(
-            "Invalid or incomplete filter expression".to_owned(),
-        ));
+        Ok((result, end_span))
     }
+}
 
-    Ok(result)
+// is_operand checks if the token could be used as an expression operand.
+pub(crate) fn is_operand(t: &Token) -> bool {
+    matches!(
+        t,
+        Token::Identifier(_) | Token::Text(_) | Token::Number(_) | Token::Bool(_) | Token::Null(_)
+    )
+}
+
+// negate wraps the item with the specified number of negations.
+//
+// A run of negations is collapsed (eg. `!!a=1` is the same as `a=1` and `!!!a=1` as `!a=1`)
+// so that an arbitrary long `!` prefix can't result in a deeply nested AST.
+fn negate(item: ExprGroupItem<'_>, negations: usize) -> ExprGroupItem<'_> {
+    if negations % 2 == 1 {
+        ExprGroupItem::Not(Box::new(item))
+    } else {
+        item
+    }
+}
+
+// unexpected creates a new error for a token that is not allowed by the current step.
+fn unexpected(t: Token<'_>, span: Span, step: &Step) -> Error {
+    Error::new(ErrorKind::UnexpectedToken, t, span).with_expected(step.expected())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use crate::{
-        parser::{parse, Expr},
-        scanner::Token,
+        error::ErrorKind,
+        params::{Param, Params},
+        parser::{
+            parse, parse_with_options, parse_with_params, Expr, ExprGroup, ExprGroupItem,
+            ExprGroups, ParseOptions,
+        },
+        scanner::{JoinOp, Token},
         SignOp,
     };
 
     #[test]
     fn test_expr_is_zero() {
         struct Scenario {
-            expr: Expr,
+            expr: Expr<'static>,
             result: bool,
         }
 
-        let scenarios = vec![
+        let scenarios = [
             Scenario {
                 expr: Expr::default(),
                 result: true,
@@ -268,28 +768,28 @@ mod tests {
             },
             Scenario {
                 expr: Expr {
-                    left: Token::Number("123".to_owned()),
+                    left: Token::Number("123".into()),
                     ..Default::default()
                 },
                 result: false,
             },
             Scenario {
                 expr: Expr {
-                    left: Token::Ws("".to_owned()),
+                    left: Token::Ws("".into()),
                     ..Default::default()
                 },
                 result: false,
             },
             Scenario {
                 expr: Expr {
-                    right: Token::Number("123".to_owned()),
+                    right: Token::Number("123".into()),
                     ..Default::default()
                 },
                 result: false,
             },
             Scenario {
                 expr: Expr {
-                    right: Token::Ws("".to_owned()),
+                    right: Token::Ws("".into()),
                     ..Default::default()
                 },
                 result: false,
@@ -575,6 +1075,16 @@ mod tests {
                 expected_error: false,
                 expected_print: r#"[{&& {{identifier demo} = {text te"st}}}]"#,
             },
+            Scenario {
+                input: r"name = 'Zoë' && title ~ '日本'",
+                expected_error: false,
+                expected_print: r"[{&& {{identifier name} = {text Zoë}}} {&& {{identifier title} ~ {text 日本}}}]",
+            },
+            Scenario {
+                input: r"(名前 != 'ü') || a=1",
+                expected_error: false,
+                expected_print: r"[{&& [{&& {{identifier 名前} != {text ü}}}]} {|| {{identifier a} = {number 1}}}]",
+            },
             // invalid parenthesis
             Scenario {
                 input: r"(a=1",
@@ -657,6 +1167,47 @@ mod tests {
                 expected_error: false,
                 expected_print: r"[{&& [{&& [{&& {{identifier a} = {number 1}}} {|| {{identifier a} = {number 2}}}]} {&& [{&& {{identifier c} = {number 1}}}]}]}]",
             },
+            // negations
+            Scenario {
+                input: r"!",
+                expected_error: true,
+                expected_print: r"[]",
+            },
+            Scenario {
+                input: r"a=1 && !",
+                expected_error: true,
+                expected_print: r"[]",
+            },
+            Scenario {
+                input: r"a=1 !",
+                expected_error: true,
+                expected_print: r"[]",
+            },
+            Scenario {
+                input: r"!a=1",
+                expected_error: false,
+                expected_print: r"[{&& !{{identifier a} = {number 1}}}]",
+            },
+            Scenario {
+                input: r"! a != 1",
+                expected_error: false,
+                expected_print: r"[{&& !{{identifier a} != {number 1}}}]",
+            },
+            Scenario {
+                input: r"!(a=1 && b=2)",
+                expected_error: false,
+                expected_print: r"[{&& ![{&& {{identifier a} = {number 1}}} {&& {{identifier b} = {number 2}}}]}]",
+            },
+            Scenario {
+                input: r"c=3 || !!(!a=1)",
+                expected_error: false,
+                expected_print: r"[{&& {{identifier c} = {number 3}}} {|| [{&& !{{identifier a} = {number 1}}}]}]",
+            },
+            Scenario {
+                input: r"!!!a=1 && !!!!(b=2)",
+                expected_error: false,
+                expected_print: r"[{&& !{{identifier a} = {number 1}}} {&& [{&& {{identifier b} = {number 2}}}]}]",
+            },
             // https://github.com/pocketbase/pocketbase/issues/5017
             Scenario {
                 input: r#"(a='"')"#,
@@ -678,6 +1229,63 @@ mod tests {
                 expected_error: false,
                 expected_print: r#"[{&& [{&& {{identifier a} = {text "}}}]}]"#,
             },
+            // list membership
+            Scenario {
+                input: r"a in ['b', 1, -2.5]",
+                expected_error: false,
+                expected_print: r"[{&& {{identifier a} in {list [{text b} {number 1} {number -2.5}]}}}]",
+            },
+            Scenario {
+                input: r"a !in [] || !(b in['c'])",
+                expected_error: false,
+                expected_print: r"[{&& {{identifier a} !in {list []}}} {|| ![{&& {{identifier b} in {list [{text c}]}}}]}]",
+            },
+            Scenario {
+                input: r"a in [true, null, 'c'] && b != false",
+                expected_error: false,
+                expected_print: r"[{&& {{identifier a} in {list [{bool true} {null null} {text c}]}}} {&& {{identifier b} != {bool false}}}]",
+            },
+            // `in` identifier
+            Scenario {
+                input: r"in = 1 && a.in > 2 && b != in && in in [in] && in !in [3]",
+                expected_error: true,
+                expected_print: r"[]",
+            },
+            Scenario {
+                input: r"in = 1 && a.in > 2 && b != in && in in [3] || !in !in [4]",
+                expected_error: false,
+                expected_print: r"[{&& {{identifier in} = {number 1}}} {&& {{identifier a.in} > {number 2}}} {&& {{identifier b} != {identifier in}}} {&& {{identifier in} in {list [{number 3}]}}} {|| !{{identifier in} !in {list [{number 4}]}}}]",
+            },
+            Scenario {
+                input: r"in in",
+                expected_error: true,
+                expected_print: r"[]",
+            },
+            Scenario {
+                input: r"deleted = null || null != a.null",
+                expected_error: false,
+                expected_print: r"[{&& {{identifier deleted} = {null null}}} {|| {{null null} != {identifier a.null}}}]",
+            },
+            Scenario {
+                input: r"a = TRUE && b = nullable",
+                expected_error: false,
+                expected_print: r"[{&& {{identifier a} = {identifier TRUE}}} {&& {{identifier b} = {identifier nullable}}}]",
+            },
+            Scenario {
+                input: r"a in 'b'",
+                expected_error: true,
+                expected_print: r"[]",
+            },
+            Scenario {
+                input: r"a = ['b']",
+                expected_error: true,
+                expected_print: r"[]",
+            },
+            Scenario {
+                input: r"['b'] in a",
+                expected_error: true,
+                expected_print: r"[]",
+            },
         ];
 
         for (i, scenario) in scenarios.iter().enumerate() {
@@ -711,4 +1319,713 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn test_expr_groups_construct() {
+        let mut nested = ExprGroups::new();
+        nested.push(ExprGroup::new(
+            JoinOp::And,
+            Expr::new(
+                Token::Identifier("b".into()),
+                SignOp::Like,
+                Token::Text("c".into()),
+            ),
+        ));
+        nested.push(ExprGroup::new(
+            JoinOp::Or,
+            ExprGroupItem::Not(Box::new(
+                Expr::new(
+                    Token::Identifier("d".into()),
+                    SignOp::In,
+                    Token::List(vec![Token::Number("1".into()), Token::Null("null".into())]),
+                )
+                .into(),
+            )),
+        ));
+
+        let built: ExprGroups = [
+            ExprGroup::new(
+                JoinOp::And,
+                Expr::new(
+                    Token::Identifier("a".into()),
+                    SignOp::Eq,
+                    Token::Bool("true".into()),
+                ),
+            ),
+            ExprGroup::new(JoinOp::And, nested),
+        ]
+        .into_iter()
+        .collect();
+
+        let parsed = parse("a = true && (b ~ 'c' || !d in [1, null])").unwrap();
+
+        assert!(
+            built == parsed,
+            "Expected {} to equal the parsed {}",
+            built,
+            parsed
+        );
+        assert!(
+            !parsed.is_empty() && parsed.len() == 2,
+            "Expected 2 groups, got {}",
+            parsed.len()
+        );
+        assert!(
+            parsed[1].item == built[1].item,
+            "Expected {}, got {}",
+            built[1].item,
+            parsed[1].item
+        );
+
+        let joins: Vec<JoinOp> = (&parsed).into_iter().map(|group| group.join).collect();
+        assert!(
+            joins == [JoinOp::And, JoinOp::And],
+            "Expected only && joins, got {:?}",
+            joins
+        );
+
+        // spans are ignored when comparing and hashing
+        let mut set = HashSet::new();
+        set.insert(built);
+        set.insert(parsed.clone());
+        assert!(
+            set.len() == 1,
+            "Expected 1 unique filter, got {}",
+            set.len()
+        );
+
+        let owned: Vec<ExprGroup<'static>> = parsed.into_owned().into_iter().collect();
+        assert!(
+            owned.len() == 2,
+            "Expected 2 owned groups, got {}",
+            owned.len()
+        );
+        assert!(
+            ExprGroups::default().is_empty(),
+            "Expected empty default groups"
+        );
+    }
+
+    #[test]
+    fn test_parse_into_owned() {
+        let text = String::from(r"a = 'b' && (c ~ 'd\'e' || f > 1)");
+        let result = parse(&text).unwrap();
+        let expected_print = result.to_string();
+
+        let owned = result.into_owned();
+        drop(text);
+
+        assert!(
+            owned.to_string() == expected_print,
+            "Expected {}, got {}",
+            expected_print,
+            owned
+        );
+    }
+
+    #[test]
+    fn test_parse_spans() {
+        let result = parse("a=1 ||\n  (b = 'c' && d>2)").unwrap();
+        let groups = result.get();
+
+        assert!(groups.len() == 2, "Expected 2 groups, got {}", groups.len());
+
+        let first = &groups[0];
+        assert!(
+            first.span.start.offset == 0 && first.span.end.offset == 3,
+            "Expected the first group span to be 0-3, got {:?}",
+            first.span
+        );
+
+        let second = &groups[1];
+        assert!(
+            second.span.start.offset == 9 && second.span.end.offset == 25,
+            "Expected the second group span to be 9-25, got {:?}",
+            second.span
+        );
+        assert!(
+            second.span.start.line == 2 && second.span.start.column == 3,
+            "Expected the second group to start at 2:3, got {}",
+            second.span.start
+        );
+
+        let nested = match &second.item {
+            ExprGroupItem::ExprGroups(nested) => nested.get(),
+            _ => panic!("Expected nested expression groups"),
+        };
+
+        let expected = [(10, 2, 4, 17, 2, 11), (21, 2, 15, 24, 2, 18)];
+        for (i, (group, expected)) in nested.iter().zip(expected).enumerate() {
+            let span = match &group.item {
+                ExprGroupItem::Expr(expr) => expr.span,
+                _ => panic!("({i}) Expected an expression"),
+            };
+            let actual = (
+                span.start.offset,
+                span.start.line,
+                span.start.column,
+                span.end.offset,
+                span.end.line,
+                span.end.column,
+            );
+
+            assert!(
+                actual == expected && group.span == span,
+                "({}) Expected span {:?}, got {:?}",
+                i,
+                expected,
+                actual
+            );
+        }
+
+        // the negated `in` identifier
+        let result = parse("!in = 1").unwrap();
+        let group = &result.get()[0];
+        let expr = match &group.item {
+            ExprGroupItem::Not(item) => match item.as_ref() {
+                ExprGroupItem::Expr(expr) => expr,
+                _ => panic!("Expected a negated expression"),
+            },
+            _ => panic!("Expected a negation"),
+        };
+        assert!(
+            group.span.start.offset == 0
+                && expr.span.start.offset == 1
+                && expr.span.start.column == 2
+                && expr.span.end.offset == 7,
+            "Unexpected spans {:?} and {:?}",
+            group.span,
+            expr.span
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        struct Scenario {
+            input: &'static str,
+            kind: ErrorKind,
+            token: Token<'static>,
+            offset: usize,
+            expected: &'static [&'static str],
+            message: &'static str,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: "",
+                kind: ErrorKind::Empty,
+                token: Token::Eof("\0".into()),
+                offset: 0,
+                expected: &[
+                    "identifier",
+                    "text",
+                    "number",
+                    "bool",
+                    "null",
+                    "placeholder",
+                    "group_start",
+                    "not",
+                ],
+                message: "empty filter expression at 1:1, expected identifier, text, number, bool, null, placeholder, group_start or not",
+            },
+            Scenario {
+                input: "a > 1 &&",
+                kind: ErrorKind::Incomplete,
+                token: Token::Eof("\0".into()),
+                offset: 8,
+                expected: &[
+                    "identifier",
+                    "text",
+                    "number",
+                    "bool",
+                    "null",
+                    "placeholder",
+                    "group_start",
+                    "not",
+                ],
+                message: "invalid or incomplete filter expression at 1:9, expected identifier, text, number, bool, null, placeholder, group_start or not",
+            },
+            Scenario {
+                input: "a > >",
+                kind: ErrorKind::UnexpectedToken,
+                token: Token::Sign(">".into()),
+                offset: 4,
+                expected: &["identifier", "text", "number", "bool", "null", "placeholder"],
+                message: "unexpected token `>` (sign) at 1:5, expected identifier, text, number, bool, null or placeholder",
+            },
+            Scenario {
+                input: "a = 1 b",
+                kind: ErrorKind::UnexpectedToken,
+                token: Token::Identifier("b".into()),
+                offset: 6,
+                expected: &["join"],
+                message: "unexpected token `b` (identifier) at 1:7, expected join",
+            },
+            Scenario {
+                input: "a 1",
+                kind: ErrorKind::UnexpectedToken,
+                token: Token::Number("1".into()),
+                offset: 2,
+                expected: &["sign"],
+                message: "unexpected token `1` (number) at 1:3, expected sign",
+            },
+            Scenario {
+                input: "a = 1 &&\n b = 12.",
+                kind: ErrorKind::InvalidNumber,
+                token: Token::Number("12.".into()),
+                offset: 14,
+                expected: &[],
+                message: "invalid number `12.` (number) at 2:6",
+            },
+            Scenario {
+                input: "a = %",
+                kind: ErrorKind::UnexpectedChar,
+                token: Token::Unexpected("%".into()),
+                offset: 4,
+                expected: &[],
+                message: "unexpected character `%` (unexpected) at 1:5",
+            },
+            Scenario {
+                input: "a = 1 || (b = 'c' && d =~ 1)",
+                kind: ErrorKind::InvalidSign,
+                token: Token::Sign("=~".into()),
+                offset: 23,
+                expected: &[],
+                message: "invalid sign operator `=~` (sign) at 1:24",
+            },
+            Scenario {
+                input: "a = 1 && !",
+                kind: ErrorKind::Incomplete,
+                token: Token::Eof("\0".into()),
+                offset: 10,
+                expected: &[
+                    "identifier",
+                    "text",
+                    "number",
+                    "bool",
+                    "null",
+                    "placeholder",
+                    "group_start",
+                    "not",
+                ],
+                message: "invalid or incomplete filter expression at 1:11, expected identifier, text, number, bool, null, placeholder, group_start or not",
+            },
+            Scenario {
+                input: "a ! 1",
+                kind: ErrorKind::UnexpectedToken,
+                token: Token::Not("!".into()),
+                offset: 2,
+                expected: &["sign"],
+                message: "unexpected token `!` (not) at 1:3, expected sign",
+            },
+            Scenario {
+                input: "a = 1 && (b = 2",
+                kind: ErrorKind::InvalidGroup,
+                token: Token::GroupStart("(".into()),
+                offset: 9,
+                expected: &["join", "group_end"],
+                message: "invalid formatted group (missing closing bracket) `(` (group_start) at 1:10, expected join or group_end",
+            },
+            Scenario {
+                input: "(a = 1))",
+                kind: ErrorKind::UnexpectedToken,
+                token: Token::GroupEnd(")".into()),
+                offset: 7,
+                expected: &["join"],
+                message: "unexpected token `)` (group_end) at 1:8, expected join",
+            },
+            Scenario {
+                input: "a = 1 || ()",
+                kind: ErrorKind::Empty,
+                token: Token::GroupEnd(")".into()),
+                offset: 10,
+                expected: &[
+                    "identifier",
+                    "text",
+                    "number",
+                    "bool",
+                    "null",
+                    "placeholder",
+                    "group_start",
+                    "not",
+                ],
+                message: "empty filter expression `)` (group_end) at 1:11, expected identifier, text, number, bool, null, placeholder, group_start or not",
+            },
+            Scenario {
+                input: "((a = 1 || b = ))",
+                kind: ErrorKind::UnexpectedToken,
+                token: Token::GroupEnd(")".into()),
+                offset: 15,
+                expected: &["identifier", "text", "number", "bool", "null", "placeholder"],
+                message: "unexpected token `)` (group_end) at 1:16, expected identifier, text, number, bool, null or placeholder",
+            },
+            Scenario {
+                input: "a = 1 (b = 2)",
+                kind: ErrorKind::UnexpectedToken,
+                token: Token::GroupStart("(".into()),
+                offset: 6,
+                expected: &["join"],
+                message: "unexpected token `(` (group_start) at 1:7, expected join",
+            },
+            Scenario {
+                input: "a in 1",
+                kind: ErrorKind::UnexpectedToken,
+                token: Token::Number("1".into()),
+                offset: 5,
+                expected: &["list", "placeholder"],
+                message: "unexpected token `1` (number) at 1:6, expected list or placeholder",
+            },
+            Scenario {
+                input: "a in [1,]",
+                kind: ErrorKind::InvalidList,
+                token: Token::List(vec![Token::Number("1".into())]),
+                offset: 5,
+                expected: &[],
+                message: "invalid list (expected comma separated text, number, bool or null literals) (list) at 1:6",
+            },
+        ];
+
+        for (i, s) in scenarios.iter().enumerate() {
+            let err = match parse(s.input) {
+                Ok(v) => panic!("({}) Expected error, got ok ({})", i, v),
+                Err(err) => err,
+            };
+
+            assert!(
+                err.kind == s.kind && err.code() == s.kind.code(),
+                "({}) Expected kind {:?}, got {:?}",
+                i,
+                s.kind,
+                err.kind
+            );
+            assert!(
+                err.token == s.token,
+                "({}) Expected token {}, got {}",
+                i,
+                s.token,
+                err.token
+            );
+            assert!(
+                err.span.start.offset == s.offset,
+                "({}) Expected offset {}, got {}",
+                i,
+                s.offset,
+                err.span.start.offset
+            );
+            assert!(
+                err.expected == s.expected,
+                "({}) Expected {:?}, got {:?}",
+                i,
+                s.expected,
+                err.expected
+            );
+            assert!(
+                err.to_string() == s.message,
+                "({}) Expected message {}, got {}",
+                i,
+                s.message,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_with_params() {
+        struct Scenario {
+            input: &'static str,
+            params: Params,
+            expected: Result<&'static str, ErrorKind>,
+        }
+
+        let scenarios = vec![
+            Scenario {
+                input: r"owner = {:userId} && status = 'active'",
+                params: Params::new().with_named("userId", "abc"),
+                expected: Ok(
+                    r"[{&& {{identifier owner} = {text abc}}} {&& {{identifier status} = {text active}}}]",
+                ),
+            },
+            Scenario {
+                input: r"a=? || {:b} > ? && c in ? && d = {:b}",
+                params: Params::new()
+                    .with_named("b", 1.5)
+                    .with_positional(true)
+                    .with_positional(-2)
+                    .with_positional(vec![Param::Null, "x".into(), 3.into()]),
+                expected: Ok(
+                    r"[{&& {{identifier a} = {bool true}}} {|| {{number 1.5} > {number -2}}} {&& {{identifier c} in {list [{null null} {text x} {number 3}]}}} {&& {{identifier d} = {number 1.5}}}]",
+                ),
+            },
+            Scenario {
+                // the values are never scanned
+                input: r"a = {:a} && b ?= ?",
+                params: Params::new()
+                    .with_named("a", "x' || 1=1 || b = 'y")
+                    .with_positional(None::<i64>),
+                expected: Ok(
+                    r"[{&& {{identifier a} = {text x' || 1=1 || b = 'y}}} {&& {{identifier b} ?= {null null}}}]",
+                ),
+            },
+            Scenario {
+                input: r"a = {:missing}",
+                params: Params::new().with_named("a", 1),
+                expected: Err(ErrorKind::MissingParam),
+            },
+            Scenario {
+                input: r"a = ? && b = ?",
+                params: Params::new().with_positional(1),
+                expected: Err(ErrorKind::MissingParam),
+            },
+            Scenario {
+                input: r"a = ?",
+                params: Params::new().with_positional(f64::NAN),
+                expected: Err(ErrorKind::InvalidNumber),
+            },
+            Scenario {
+                input: r"a in ?",
+                params: Params::new().with_positional(Param::List(vec![Param::List(vec![])])),
+                expected: Err(ErrorKind::InvalidList),
+            },
+            Scenario {
+                input: r"a = ?",
+                params: Params::new().with_positional(vec![1]),
+                expected: Err(ErrorKind::UnexpectedToken),
+            },
+            Scenario {
+                input: r"a in ?",
+                params: Params::new().with_positional(1),
+                expected: Err(ErrorKind::UnexpectedToken),
+            },
+            Scenario {
+                input: r"a ? 1",
+                params: Params::new().with_positional("="),
+                expected: Err(ErrorKind::UnexpectedToken),
+            },
+            Scenario {
+                input: r"a = {:1}",
+                params: Params::new(),
+                expected: Err(ErrorKind::InvalidPlaceholder),
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let result = parse_with_params(scenario.input, &scenario.params)
+                .map(|v| v.to_string())
+                .map_err(|err| err.kind);
+
+            assert!(
+                result.as_deref() == scenario.expected.as_deref(),
+                "({}) Expected {:?}, got {:?}",
+                i,
+                scenario.expected,
+                result
+            );
+        }
+
+        // placeholders are not allowed without params
+        let err = parse("a = {:a}").unwrap_err();
+        assert!(
+            err.to_string() == "missing placeholder parameter `{:a}` (placeholder) at 1:5",
+            "Unexpected error {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_parse_with_options() {
+        struct Scenario {
+            input: String,
+            options: ParseOptions,
+            expected: Result<&'static str, ErrorKind>,
+        }
+
+        let scenarios = vec![
+            Scenario {
+                input: r"a = 'abc' && (b > 1 || !(c ~ d))".to_string(),
+                options: ParseOptions {
+                    max_len: Some(32),
+                    max_depth: Some(3),
+                    max_exprs: Some(3),
+                    max_text_len: Some(3),
+                    ..Default::default()
+                },
+                expected: Ok(
+                    r"[{&& {{identifier a} = {text abc}}} {&& [{&& {{identifier b} > {number 1}}} {|| ![{&& {{identifier c} ~ {identifier d}}}]}]}]",
+                ),
+            },
+            Scenario {
+                input: r"a = 'abc' && (b > 1 || !(c ~ d))".to_string(),
+                options: ParseOptions {
+                    max_len: Some(31),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TooLong),
+            },
+            Scenario {
+                // the length is checked before scanning the invalid input
+                input: format!("{}a = 1", "@".repeat(100)),
+                options: ParseOptions {
+                    max_len: Some(10),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TooLong),
+            },
+            Scenario {
+                input: r"a = 'abc' && (b > 1 || !(c ~ d))".to_string(),
+                options: ParseOptions {
+                    max_depth: Some(1),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TooDeep),
+            },
+            Scenario {
+                input: r"(a = 1)".to_string(),
+                options: ParseOptions {
+                    max_depth: Some(0),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TooDeep),
+            },
+            Scenario {
+                input: r"a = 'abc' && (b > 1 || !(c ~ d))".to_string(),
+                options: ParseOptions {
+                    max_depth: Some(2),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TooDeep),
+            },
+            Scenario {
+                input: r"!!a = 1".to_string(),
+                options: ParseOptions {
+                    max_depth: Some(2),
+                    ..Default::default()
+                },
+                expected: Ok(r"[{&& {{identifier a} = {number 1}}}]"),
+            },
+            Scenario {
+                input: r"!!!a = 1".to_string(),
+                options: ParseOptions {
+                    max_depth: Some(2),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TooDeep),
+            },
+            Scenario {
+                // a stack overflow without the depth limit
+                input: format!("{}a = 1{}", "(".repeat(100_000), ")".repeat(100_000)),
+                options: ParseOptions {
+                    max_depth: Some(64),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TooDeep),
+            },
+            Scenario {
+                input: format!("{}a=1", "!".repeat(1_000_000)),
+                options: ParseOptions {
+                    max_len: Some(10_000_000),
+                    max_depth: Some(64),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TooDeep),
+            },
+            Scenario {
+                // the depth is limited by default
+                input: format!("{}a=1", "!".repeat(1_000_000)),
+                options: ParseOptions::default(),
+                expected: Err(ErrorKind::TooDeep),
+            },
+            Scenario {
+                input: r"a = 'abc' && (b > 1 || !(c ~ d))".to_string(),
+                options: ParseOptions {
+                    max_exprs: Some(2),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TooManyExprs),
+            },
+            Scenario {
+                input: r"a = 'abcd'".to_string(),
+                options: ParseOptions {
+                    max_text_len: Some(3),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TextTooLong),
+            },
+            Scenario {
+                input: r"a in ['abc', 'abcd']".to_string(),
+                options: ParseOptions {
+                    max_text_len: Some(3),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TextTooLong),
+            },
+            Scenario {
+                input: r"a = {:a} && b = ?".to_string(),
+                options: ParseOptions {
+                    max_text_len: Some(3),
+                    params: Params::new().with_named("a", "abc").with_positional(1),
+                    ..Default::default()
+                },
+                expected: Ok(
+                    r"[{&& {{identifier a} = {text abc}}} {&& {{identifier b} = {number 1}}}]",
+                ),
+            },
+            Scenario {
+                input: r"a = {:a}".to_string(),
+                options: ParseOptions {
+                    max_text_len: Some(3),
+                    params: Params::new().with_named("a", "abcd"),
+                    ..Default::default()
+                },
+                expected: Err(ErrorKind::TextTooLong),
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let result = parse_with_options(&scenario.input, &scenario.options)
+                .map(|v| v.to_string())
+                .map_err(|err| err.kind);
+
+            assert!(
+                result.as_deref() == scenario.expected.as_deref(),
+                "({}) Expected {:?}, got {:?}",
+                i,
+                scenario.expected,
+                result
+            );
+        }
+
+        let err = parse_with_options(
+            "a = 1 && ((b = 2))",
+            &ParseOptions {
+                max_depth: Some(1),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                == "group or negation exceeds the max allowed nesting depth `(` (group_start) at 1:11",
+            "Unexpected error {}",
+            err
+        );
+
+        // a stack overflow without the default depth limit
+        let input = format!("{}a = 1{}", "(".repeat(100_000), ")".repeat(100_000));
+        let err = parse(&input).unwrap_err();
+        assert!(
+            err.kind == ErrorKind::TooDeep,
+            "Expected {:?}, got {:?}",
+            ErrorKind::TooDeep,
+            err.kind
+        );
+
+        // the default depth limit could be disabled
+        let input = format!("{}a = 1{}", "(".repeat(200), ")".repeat(200));
+        let options = ParseOptions {
+            max_depth: None,
+            ..Default::default()
+        };
+        let result = parse_with_options(&input, &options);
+        assert!(result.is_ok(), "Expected Ok, got {:?}", result);
+    }
 }
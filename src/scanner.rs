@@ -1,15 +1,23 @@
-use std::io::{BufReader, Read};
+use std::borrow::Cow;
 
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::{bytes, error::Error};
+use crate::{
+    error::{Error, ErrorKind},
+    span::{Position, Span},
+};
 
 // EOF represents a marker char for the end of the reader.
 const EOF: char = '\0';
 
 // JoinOp represents a join type operator.
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum JoinOp {
     // supported join type operators
     And,
@@ -17,6 +25,7 @@ pub enum JoinOp {
 }
 
 impl JoinOp {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(str: &str) -> Option<Self> {
         match str {
             "&&" => Some(Self::And),
@@ -40,7 +49,7 @@ impl std::fmt::Display for JoinOp {
 }
 
 // SignOp represents an expression sign operator.
-#[derive(Default, PartialEq, Clone)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SignOp {
     // supported expression sign operators
     #[default]
@@ -62,9 +71,13 @@ pub enum SignOp {
     AnyLte,
     AnyGt,
     AnyGte,
+    // supported expression sign operators - list membership operators
+    In,
+    Nin,
 }
 
 impl SignOp {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(str: &str) -> Option<Self> {
         match str {
             "=" => Some(Self::Eq),
@@ -83,6 +96,8 @@ impl SignOp {
             "?<=" => Some(Self::AnyLte),
             "?>" => Some(Self::AnyGt),
             "?>=" => Some(Self::AnyGte),
+            "in" => Some(Self::In),
+            "!in" => Some(Self::Nin),
             _ => None,
         }
     }
@@ -106,6 +121,8 @@ impl SignOp {
             Self::AnyLte => "?<=",
             Self::AnyGt => "?>",
             Self::AnyGte => "?>=",
+            Self::In => "in",
+            Self::Nin => "!in",
         }
     }
 }
@@ -116,43 +133,78 @@ impl std::fmt::Display for SignOp {
     }
 }
 
-// Token represents a token kind and its literal.
 // Token represents a single scanned literal (one or more combined chars).
-#[derive(Default, PartialEq, Clone)]
-pub enum Token {
+//
+// The literals are borrowed from the scanned input whenever possible
+// (only the unescaped quoted text literals are allocated).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Token<'a> {
     // token kind constants
     #[default]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "crate::serialize::serialize_none",
+            deserialize_with = "crate::serialize::deserialize_none"
+        )
+    )]
     None,
-    Eof(String),
-    Ws(String),
-    Join(String),
-    Sign(String),
-    Identifier(String),
-    Number(String),
-    Text(String),
-    Group(String),
-    Comment(String),
+    Eof(Cow<'a, str>),
+    #[cfg_attr(feature = "serde", serde(rename = "whitespace"))]
+    Ws(Cow<'a, str>),
+    Join(Cow<'a, str>),
+    Sign(Cow<'a, str>),
+    Identifier(Cow<'a, str>),
+    Number(Cow<'a, str>),
+    Text(Cow<'a, str>),
+    Bool(Cow<'a, str>),
+    Null(Cow<'a, str>),
+    Group(Cow<'a, str>),
+    GroupStart(Cow<'a, str>),
+    GroupEnd(Cow<'a, str>),
+    Not(Cow<'a, str>),
+    List(Vec<Token<'a>>),
+    Placeholder(Cow<'a, str>),
+    Comment(Cow<'a, str>),
+    Unexpected(Cow<'a, str>),
 }
 
-impl Token {
-    pub fn kind(&self) -> &str {
+impl<'a> Token<'a> {
+    pub fn kind(&self) -> &'static str {
         match self {
             Self::None => "",
             Self::Eof(_) => "eof",
             Self::Ws(_) => "whitespace",
             Self::Join(_) => "join",
             Self::Sign(_) => "sign",
-            Self::Identifier(_) => "identifier", // variable, column name, placeholder, etc.
+            Self::Identifier(_) => "identifier", // variable, column name, etc.
             Self::Number(_) => "number",
-            Self::Text(_) => "text",   // ' or " quoted string
-            Self::Group(_) => "group", // groupped/nested tokens
+            Self::Text(_) => "text", // ' or " quoted string
+            Self::Bool(_) => "bool", // true or false
+            Self::Null(_) => "null",
+            Self::Group(_) => "group", // groupped/nested tokens (available only with group literals)
+            Self::GroupStart(_) => "group_start",
+            Self::GroupEnd(_) => "group_end",
+            Self::Not(_) => "not", // prefix negation of a group or an expression
+            Self::List(_) => "list", // bracketed list of text, number, bool and null literals
+            Self::Placeholder(_) => "placeholder", // {:name} or positional ?
             Self::Comment(_) => "comment",
+            Self::Unexpected(_) => "unexpected", // unknown char (available only in errors)
         }
     }
 
+    // literal returns the token literal.
+    //
+    // List tokens don't have a single literal (use `Token::items` instead).
     pub fn literal(&self) -> &str {
         match self {
             Self::None => "",
+            Self::List(_) => "",
             Self::Eof(value) => value,
             Self::Ws(value) => value,
             Self::Join(value) => value,
@@ -160,43 +212,199 @@ impl Token {
             Self::Identifier(value) => value,
             Self::Number(value) => value,
             Self::Text(value) => value,
+            Self::Bool(value) => value,
+            Self::Null(value) => value,
             Self::Group(value) => value,
+            Self::GroupStart(value) => value,
+            Self::GroupEnd(value) => value,
+            Self::Not(value) => value,
+            Self::Placeholder(value) => value,
             Self::Comment(value) => value,
+            Self::Unexpected(value) => value,
+        }
+    }
+
+    // items returns the list token items (or an empty slice for the other tokens).
+    pub fn items(&self) -> &[Token<'a>] {
+        match self {
+            Self::List(items) => items,
+            _ => &[],
+        }
+    }
+
+    // into_owned converts the token into one that doesn't borrow from the scanned input.
+    pub fn into_owned(self) -> Token<'static> {
+        match self {
+            Self::None => Token::None,
+            Self::List(items) => Token::List(items.into_iter().map(Token::into_owned).collect()),
+            Self::Eof(value) => Token::Eof(Cow::Owned(value.into_owned())),
+            Self::Ws(value) => Token::Ws(Cow::Owned(value.into_owned())),
+            Self::Join(value) => Token::Join(Cow::Owned(value.into_owned())),
+            Self::Sign(value) => Token::Sign(Cow::Owned(value.into_owned())),
+            Self::Identifier(value) => Token::Identifier(Cow::Owned(value.into_owned())),
+            Self::Number(value) => Token::Number(Cow::Owned(value.into_owned())),
+            Self::Text(value) => Token::Text(Cow::Owned(value.into_owned())),
+            Self::Bool(value) => Token::Bool(Cow::Owned(value.into_owned())),
+            Self::Null(value) => Token::Null(Cow::Owned(value.into_owned())),
+            Self::Group(value) => Token::Group(Cow::Owned(value.into_owned())),
+            Self::GroupStart(value) => Token::GroupStart(Cow::Owned(value.into_owned())),
+            Self::GroupEnd(value) => Token::GroupEnd(Cow::Owned(value.into_owned())),
+            Self::Not(value) => Token::Not(Cow::Owned(value.into_owned())),
+            Self::Placeholder(value) => Token::Placeholder(Cow::Owned(value.into_owned())),
+            Self::Comment(value) => Token::Comment(Cow::Owned(value.into_owned())),
+            Self::Unexpected(value) => Token::Unexpected(Cow::Owned(value.into_owned())),
         }
     }
 }
 
-impl std::fmt::Display for Token {
+impl std::fmt::Display for Token<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Self::List(items) = self {
+            write!(f, "{{{} [", self.kind())?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{item}")?;
+            }
+            return write!(f, "]}}");
+        }
+
         write!(f, "{{{} {}}}", self.kind(), self.literal())
     }
 }
 
 // Scanner represents a filter and lexical scanner.
-pub struct Scanner {
-    buffer: Vec<u8>,
+//
+// The scanner doesn't copy its input and all scanned tokens
+// borrow their literals from it.
+//
+// By default each parenthesis is scanned as a separate `GroupStart`/`GroupEnd`
+// token. Enable the group literals to scan everything within the parenthesis
+// as a single `Group` token instead.
+pub struct Scanner<'a> {
+    input: &'a str,
     pos: usize,
+    // current line and column in the input
+    line: usize,
+    column: usize,
+    // column of the last read new line char (so that it could be unread in O(1))
+    newline_column: Option<usize>,
+    group_literals: bool,
 }
 
-impl Scanner {
-    pub fn new(mut r: BufReader<impl Read>) -> Result<Self, Error> {
-        let mut buffer = Vec::new();
-        r.read_to_end(&mut buffer)
-            .map_err(|err| Error::Buffer(err.to_string()))?;
-        Ok(Scanner { buffer, pos: 0 })
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Scanner {
+            input,
+            pos: 0,
+            line: 1,
+            column: 1,
+            newline_column: None,
+            group_literals: false,
+        }
     }
 
-    pub fn scan(&mut self) -> Result<Token, Error> {
-        let ch = self.read();
+    // from_bytes creates a new scanner from the provided bytes
+    // after checking that they are a valid UTF-8 text.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, Error> {
+        let err = match std::str::from_utf8(bytes) {
+            Ok(input) => return Ok(Self::new(input)),
+            Err(err) => err,
+        };
+
+        let valid_up_to = err.valid_up_to();
+        let invalid_len = err.error_len().unwrap_or(bytes.len() - valid_up_to);
+        let literal = String::from_utf8_lossy(&bytes[valid_up_to..valid_up_to + invalid_len]);
+
+        // locate the invalid sequence using only the valid part of the input
+        let valid = Self::new(std::str::from_utf8(&bytes[..valid_up_to]).unwrap_or_default());
+        let start = valid.position_at(valid_up_to);
+        let end = Position {
+            offset: start.offset + invalid_len,
+            column: start.column + 1,
+            ..start
+        };
+
+        Err(Error::new(
+            ErrorKind::InvalidUtf8,
+            Token::Unexpected(literal),
+            Span::new(start, end),
+        ))
+    }
 
-        if is_whitespace_char(ch) {
-            self.unread();
+    // with_group_literals enables or disables the scanning of
+    // the parenthesis content as a single `Group` token.
+    pub fn with_group_literals(mut self, enabled: bool) -> Self {
+        self.group_literals = enabled;
+        self
+    }
+
+    pub fn scan(&mut self) -> Result<Token<'a>, Error> {
+        self.scan_spanned().map(|(token, _)| token)
+    }
+
+    // scan_spanned scans the next token and returns it together with
+    // its location in the original input.
+    pub fn scan_spanned(&mut self) -> Result<(Token<'a>, Span), Error> {
+        let start = self.position();
+        let token = self.scan_token()?;
+        Ok((token, Span::new(start, self.position())))
+    }
+
+    // position returns the current position in the input.
+    fn position(&self) -> Position {
+        Position {
+            offset: self.pos,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    // position_at calculates the position of the provided input offset.
+    fn position_at(&self, offset: usize) -> Position {
+        let before = &self.input[..offset];
+        let (line, line_start) = match before.rfind('\n') {
+            Some(i) => (before.matches('\n').count() + 1, i + 1),
+            None => (1, 0),
+        };
+
+        Position {
+            offset,
+            line,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    // literal returns the input slice between the start position and the current one.
+    fn literal(&self, start: Position) -> &'a str {
+        &self.input[start.offset..self.pos]
+    }
+
+    // error creates a new error for the token scanned from the start position.
+    fn error(&self, kind: ErrorKind, token: Token<'_>, start: Position) -> Error {
+        Error::new(kind, token, Span::new(start, self.position()))
+    }
+
+    fn scan_token(&mut self) -> Result<Token<'a>, Error> {
+        if is_whitespace_char(self.peek()) {
             return self.scan_whitespace();
         }
 
+        let start = self.position();
+        let ch = self.read();
+
         if is_group_start_char(ch) {
-            self.unread();
-            return self.scan_group();
+            if self.group_literals {
+                self.unread();
+                return self.scan_group();
+            }
+
+            return Ok(Token::GroupStart(Cow::Borrowed(self.literal(start))));
+        }
+
+        if is_group_end_char(ch) && !self.group_literals {
+            return Ok(Token::GroupEnd(Cow::Borrowed(self.literal(start))));
         }
 
         if is_identifier_start_char(ch) {
@@ -214,6 +422,30 @@ impl Scanner {
             return self.scan_text(false);
         }
 
+        if is_not_char(ch) && self.peek_keyword(SignOp::In.as_str()) {
+            self.skip(SignOp::In.as_str().len());
+            return Ok(Token::Sign(Cow::Borrowed(self.literal(start))));
+        }
+
+        if is_not_char(ch) && !is_negated_sign_char(self.peek()) {
+            return Ok(Token::Not(Cow::Borrowed(self.literal(start))));
+        }
+
+        if is_list_start_char(ch) {
+            self.unread();
+            return self.scan_list();
+        }
+
+        if is_placeholder_start_char(ch) {
+            self.unread();
+            return self.scan_placeholder();
+        }
+
+        // a standalone `?` (aka. not followed by a sign char like in `?=`)
+        if is_positional_placeholder_char(ch) && !is_sign_start_char(self.peek()) {
+            return Ok(Token::Placeholder(Cow::Borrowed(self.literal(start))));
+        }
+
         if is_sign_start_char(ch) {
             self.unread();
             return self.scan_sign();
@@ -230,41 +462,33 @@ impl Scanner {
         }
 
         if ch == EOF {
-            return Ok(Token::Eof(ch.to_string()));
+            return Ok(Token::Eof(Cow::Borrowed("\0")));
         }
 
-        Err(Error::Unexpected(format!("Unexpected character {ch}")))
+        Err(self.error(
+            ErrorKind::UnexpectedChar,
+            Token::Unexpected(Cow::Borrowed(self.literal(start))),
+            start,
+        ))
     }
 
-    fn scan_whitespace(&mut self) -> Result<Token, Error> {
-        let mut buf = bytes::Buffer::new();
+    fn scan_whitespace(&mut self) -> Result<Token<'a>, Error> {
+        let start = self.position();
 
-        // Reads every subsequent whitespace character into the buffer.
+        // Reads every subsequent whitespace character.
         // Non-whitespace chars and EOF will cause the loop to exit.
-        loop {
-            let ch = self.read();
-
-            if ch == EOF {
-                break;
-            }
-
-            if !is_whitespace_char(ch) {
-                self.unread();
-                break;
-            }
-
-            // write the whitespace char
-            buf.write_char(ch)?;
+        while is_whitespace_char(self.peek()) {
+            self.read();
         }
 
-        Ok(Token::Ws(buf.into_string()?))
+        Ok(Token::Ws(Cow::Borrowed(self.literal(start))))
     }
 
     // scanIdentifier consumes all contiguous ident chars.
-    fn scan_identifier(&mut self) -> Result<Token, Error> {
-        let mut buf = bytes::Buffer::new();
+    fn scan_identifier(&mut self) -> Result<Token<'a>, Error> {
+        let start = self.position();
 
-        // Read every subsequent identifier char into the buffer.
+        // Read every subsequent identifier char.
         // Non-ident chars and EOF will cause the loop to exit.
         loop {
             let ch = self.read();
@@ -277,28 +501,42 @@ impl Scanner {
                 self.unread();
                 break;
             }
+        }
+
+        let literal = self.literal(start);
 
-            // write the ident char
-            buf.write_char(ch)?
+        // keyword operators
+        if is_sign_operator(literal) {
+            return Ok(Token::Sign(Cow::Borrowed(literal)));
         }
 
-        let literal = buf.into_string()?;
+        // keyword literals
+        if is_bool(literal) {
+            return Ok(Token::Bool(Cow::Borrowed(literal)));
+        }
+        if is_null(literal) {
+            return Ok(Token::Null(Cow::Borrowed(literal)));
+        }
 
-        if !is_identifier(&literal) {
-            return Err(Error::Invalid(format!("Invalid identifier {literal}")));
+        if !is_identifier(literal) {
+            return Err(self.error(
+                ErrorKind::InvalidIdentifier,
+                Token::Identifier(Cow::Borrowed(literal)),
+                start,
+            ));
         }
 
-        Ok(Token::Identifier(literal))
+        Ok(Token::Identifier(Cow::Borrowed(literal)))
     }
 
     // scanNumber consumes all contiguous digit chars.
-    fn scan_number(&mut self) -> Result<Token, Error> {
-        let mut buf = bytes::Buffer::new();
+    fn scan_number(&mut self) -> Result<Token<'a>, Error> {
+        let start = self.position();
 
         // read the number first char to skip the sign (if exist)
-        buf.write_char(self.read())?;
+        self.read();
 
-        // Read every subsequent digit char into the buffer.
+        // Read every subsequent digit char.
         // Non-digit chars and EOF will cause the loop to exit.
         loop {
             let ch = self.read();
@@ -311,30 +549,31 @@ impl Scanner {
                 self.unread();
                 break;
             }
-
-            // write the digit char
-            buf.write_char(ch)?;
         }
 
-        let literal = buf.into_string()?;
+        let literal = self.literal(start);
 
-        if !is_number(&literal) {
-            return Err(Error::Invalid(format!("Invalid number {literal}")));
+        if !is_number(literal) {
+            return Err(self.error(
+                ErrorKind::InvalidNumber,
+                Token::Number(Cow::Borrowed(literal)),
+                start,
+            ));
         }
-        Ok(Token::Number(literal))
+
+        Ok(Token::Number(Cow::Borrowed(literal)))
     }
 
     // scanText consumes all contiguous quoted text chars.
-    fn scan_text(&mut self, preserve_quotes: bool) -> Result<Token, Error> {
-        let mut buf = bytes::Buffer::new();
+    fn scan_text(&mut self, preserve_quotes: bool) -> Result<Token<'a>, Error> {
+        let start = self.position();
 
         // read the first char to determine the quotes type
         let first_ch = self.read();
-        buf.write_char(first_ch)?;
         let mut prev_ch = '\0';
         let mut has_matching_quotes = false;
 
-        // Read every subsequent text char into the buffer.
+        // Read every subsequent text char.
         // EOF and matching unescaped ending quote will cause the loop to exit.
         loop {
             let ch = self.read();
@@ -343,9 +582,6 @@ impl Scanner {
                 break;
             }
 
-            // write the text char
-            buf.write_char(ch)?;
-
             // unescaped matching quote, aka. the end
             if ch == first_ch && prev_ch != '\\' {
                 has_matching_quotes = true;
@@ -355,26 +591,38 @@ impl Scanner {
             prev_ch = ch;
         }
 
-        let mut literal = buf.into_string()?;
+        let literal = self.literal(start);
 
         if !has_matching_quotes {
-            return Err(Error::Invalid(format!("Invalid quoted text {literal}")));
-        } else if !preserve_quotes {
-            // unquote
-            literal = literal[1..literal.len() - 1].to_string();
-            // remove escaped quotes prefix (aka. \)
-            let first_ch_str = first_ch.to_string();
-            literal = literal.replace(&("\\".to_owned() + &first_ch_str), &first_ch_str);
+            return Err(self.error(
+                ErrorKind::InvalidText,
+                Token::Text(Cow::Borrowed(literal)),
+                start,
+            ));
+        } else if preserve_quotes {
+            return Ok(Token::Text(Cow::Borrowed(literal)));
+        }
+
+        // unquote
+        let quote = &literal[..1];
+        let unquoted = &literal[1..literal.len() - 1];
+
+        // remove escaped quotes prefix (aka. \)
+        let escaped_quote = if first_ch == '"' { "\\\"" } else { "\\'" };
+        if unquoted.contains(escaped_quote) {
+            return Ok(Token::Text(Cow::Owned(
+                unquoted.replace(escaped_quote, quote),
+            )));
         }
 
-        Ok(Token::Text(literal))
+        Ok(Token::Text(Cow::Borrowed(unquoted)))
     }
 
     // scan_sign consumes all contiguous sign operator chars.
-    fn scan_sign(&mut self) -> Result<Token, Error> {
-        let mut buf = bytes::Buffer::new();
+    fn scan_sign(&mut self) -> Result<Token<'a>, Error> {
+        let start = self.position();
 
-        // Read every subsequent sign char into the buffer.
+        // Read every subsequent sign char.
         // Non-sign chars and EOF will cause the loop to exit.
         loop {
             let ch = self.read();
@@ -383,29 +631,33 @@ impl Scanner {
                 break;
             }
 
-            if !is_sign_start_char(ch) {
+            // `?` could be only the first sign char (eg. `a=?` is `=` followed by a placeholder)
+            if !is_sign_start_char(ch)
+                || (is_positional_placeholder_char(ch) && self.pos - 1 > start.offset)
+            {
                 self.unread();
                 break;
             }
-
-            // write the sign char
-            buf.write_char(ch)?;
         }
 
-        let literal = buf.into_string()?;
+        let literal = self.literal(start);
 
-        if !is_sign_operator(&literal) {
-            return Err(Error::Invalid(format!("Invalid sign operator {literal}")));
+        if !is_sign_operator(literal) {
+            return Err(self.error(
+                ErrorKind::InvalidSign,
+                Token::Sign(Cow::Borrowed(literal)),
+                start,
+            ));
         }
 
-        Ok(Token::Sign(literal))
+        Ok(Token::Sign(Cow::Borrowed(literal)))
     }
 
     // scan_join consumes all contiguous join operator chars.
-    fn scan_join(&mut self) -> Result<Token, Error> {
-        let mut buf = bytes::Buffer::new();
+    fn scan_join(&mut self) -> Result<Token<'a>, Error> {
+        let start = self.position();
 
-        // Read every subsequent join operator char into the buffer.
+        // Read every subsequent join operator char.
         // Non-join chars and EOF will cause the loop to exit.
         loop {
             let ch = self.read();
@@ -418,81 +670,194 @@ impl Scanner {
                 self.unread();
                 break;
             }
-
-            // write the join operator char
-            buf.write_char(ch)?;
         }
 
-        let literal = buf.into_string()?;
+        let literal = self.literal(start);
 
-        if !is_join_operator(&literal) {
-            return Err(Error::Invalid(format!("Invalid join operator {literal}",)));
+        if !is_join_operator(literal) {
+            return Err(self.error(
+                ErrorKind::InvalidJoin,
+                Token::Join(Cow::Borrowed(literal)),
+                start,
+            ));
         }
 
-        Ok(Token::Join(literal))
+        Ok(Token::Join(Cow::Borrowed(literal)))
     }
 
     // scanGroup consumes all chars within a group/parenthesis.
-    fn scan_group(&mut self) -> Result<Token, Error> {
-        let mut buf = bytes::Buffer::new();
+    fn scan_group(&mut self) -> Result<Token<'a>, Error> {
+        let start = self.position();
 
-        // read the first group bracket without writing it to the buffer
+        // read the first group bracket without including it in the literal
         let first_char = self.read();
+        let content_start = self.pos;
+        let mut content_end = self.pos;
         let mut open_groups = 1;
 
-        // Read every subsequent text char into the buffer.
+        // Read every subsequent text char.
         // EOF and matching unescaped ending quote will cause the loop to exit.
         loop {
             let ch = self.read();
 
             if ch == EOF {
+                content_end = self.pos;
                 break;
             }
 
             if is_group_start_char(ch) {
                 open_groups += 1;
-                buf.write_char(ch)?;
             } else if is_text_start_char(ch) {
                 self.unread();
-                let t = self.scan_text(true)?; // with quotes to preserve the exact text start/end runes
-
-                buf.write_string(t.literal())?
-            } else if ch == ')' {
+                self.scan_text(true)?; // with quotes to skip the exact text start/end runes
+            } else if is_group_end_char(ch) {
                 open_groups -= 1;
 
                 if open_groups <= 0 {
                     // main group end
                     break;
-                } else {
-                    buf.write_char(ch)?;
                 }
-            } else {
-                buf.write_char(ch)?;
             }
+
+            content_end = self.pos;
         }
 
-        let literal = buf.into_string()?;
+        let literal = &self.input[content_start..content_end];
 
         if !is_group_start_char(first_char) || open_groups > 0 {
-            return Err(Error::Invalid(format!(
-                "Invalid formatted group - missing {open_groups} closing bracket(s)"
-            )));
+            return Err(self.error(
+                ErrorKind::InvalidGroup,
+                Token::Group(Cow::Borrowed(literal)),
+                start,
+            ));
         }
 
-        Ok(Token::Group(literal))
+        Ok(Token::Group(Cow::Borrowed(literal)))
+    }
+
+    // scan_list consumes a bracketed list of comma separated text, number, bool and null literals.
+    fn scan_list(&mut self) -> Result<Token<'a>, Error> {
+        let start = self.position();
+        let mut items = Vec::new();
+
+        // read the list opening bracket
+        self.read();
+
+        // whether an item (aka. after the opening bracket or a comma)
+        // or a separator/closing bracket is expected
+        let mut expect_item = true;
+
+        loop {
+            let mut ch = self.read();
+
+            if is_whitespace_char(ch) {
+                continue;
+            }
+
+            if is_list_end_char(ch) && (!expect_item || items.is_empty()) {
+                break;
+            }
+
+            if ch == ',' && !expect_item {
+                expect_item = true;
+                continue;
+            }
+
+            if expect_item && is_text_start_char(ch) {
+                self.unread();
+                items.push(self.scan_text(false)?);
+                expect_item = false;
+                continue;
+            }
+
+            if expect_item && is_number_start_char(ch) {
+                self.unread();
+                items.push(self.scan_number()?);
+                expect_item = false;
+                continue;
+            }
+
+            if expect_item && is_identifier_start_char(ch) {
+                self.unread();
+                let item = self.scan_identifier()?;
+                if matches!(item, Token::Bool(_) | Token::Null(_)) {
+                    items.push(item);
+                    expect_item = false;
+                    continue;
+                }
+                ch = self.read();
+            }
+
+            // skip the rest of the invalid list
+            while !is_list_end_char(ch) && ch != EOF {
+                ch = self.read();
+            }
+
+            return Err(self.error(ErrorKind::InvalidList, Token::List(items), start));
+        }
+
+        Ok(Token::List(items))
+    }
+
+    // scan_placeholder consumes a named placeholder (aka. `{:name}`).
+    fn scan_placeholder(&mut self) -> Result<Token<'a>, Error> {
+        let start = self.position();
+
+        // skip the opening bracket
+        self.skip(1);
+
+        let mut ch = self.read();
+        let mut valid = ch == ':';
+        let mut name_len = 0;
+
+        // Read every subsequent placeholder name char.
+        // The closing bracket, invalid chars and EOF will cause the loop to exit.
+        while valid {
+            ch = self.read();
+
+            if is_placeholder_end_char(ch) {
+                break;
+            }
+
+            valid = ch == '_' || ch.is_ascii_alphabetic() || (name_len > 0 && is_digit_char(ch));
+            name_len += 1;
+        }
+
+        if !valid || name_len == 0 {
+            // skip the rest of the invalid placeholder
+            while !is_placeholder_end_char(ch) && ch != EOF {
+                ch = self.read();
+            }
+
+            let literal = self.literal(start);
+            return Err(self.error(
+                ErrorKind::InvalidPlaceholder,
+                Token::Placeholder(Cow::Borrowed(literal)),
+                start,
+            ));
+        }
+
+        Ok(Token::Placeholder(Cow::Borrowed(self.literal(start))))
     }
 
     // scan_comment consumes all contiguous single line comment chars until
     // a new character (\n) or EOF is reached.
-    fn scan_comment(&mut self) -> Result<Token, Error> {
-        let mut buf = bytes::Buffer::new();
+    fn scan_comment(&mut self) -> Result<Token<'a>, Error> {
+        let start = self.position();
 
-        // Read the first 2 characters without writting them to the buffer.
+        // Read the first 2 characters without including them in the literal.
         if !is_comment_start_char(self.read()) || !is_comment_start_char(self.read()) {
-            return Err(Error::Invalid("Invalid comment".to_owned()));
+            return Err(self.error(
+                ErrorKind::InvalidComment,
+                Token::Comment(Cow::Borrowed("")),
+                start,
+            ));
         }
 
-        // Read every subsequent comment text char into the buffer.
+        let content_start = self.pos;
+        let mut content_end = self.pos;
+
+        // Read every subsequent comment text char.
         // \n and EOF will cause the loop to exit.
         loop {
             let ch = self.read();
@@ -501,29 +866,79 @@ impl Scanner {
                 break;
             }
 
-            buf.write_char(ch)?;
+            content_end = self.pos;
         }
 
-        let literal = buf.into_string()?;
+        let literal = &self.input[content_start..content_end];
 
-        Ok(Token::Comment(literal.trim().to_owned()))
+        Ok(Token::Comment(Cow::Borrowed(literal.trim())))
     }
 
-    // read reads the next char from the buffered reader.
-    // Returns the `\0` if an error occurs.
+    // read reads the next (possibly multi-byte) char from the input.
+    // Returns the `\0` if the end of the input is reached.
     fn read(&mut self) -> char {
-        if self.pos == self.buffer.len() {
-            return EOF;
+        match self.input[self.pos..].chars().next() {
+            Some(ch) => {
+                self.pos += ch.len_utf8();
+
+                if ch == '\n' {
+                    self.newline_column = Some(self.column);
+                    self.line += 1;
+                    self.column = 1;
+                } else {
+                    self.column += 1;
+                }
+
+                ch
+            }
+            None => EOF,
+        }
+    }
+
+    // peek returns the next char without consuming it.
+    fn peek(&self) -> char {
+        self.input[self.pos..].chars().next().unwrap_or(EOF)
+    }
+
+    // peek_keyword checks if the next chars form the provided keyword
+    // (aka. they are not followed by another identifier char).
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        match self.input[self.pos..].strip_prefix(keyword) {
+            Some(rest) => !rest.starts_with(|ch| {
+                is_identifier_start_char(ch) || is_digit_char(ch) || ch == '.' || ch == ':'
+            }),
+            None => false,
         }
-        let ch = char::from(self.buffer[self.pos]);
-        self.pos += 1;
-        ch
     }
 
-    // unread places the previously read char back on the reader.
+    // skip consumes the specified number of chars.
+    fn skip(&mut self, chars: usize) {
+        for _ in 0..chars {
+            self.read();
+        }
+    }
+
+    // unread places the previously read char back on the input.
     fn unread(&mut self) {
-        if self.pos > 0 {
-            self.pos -= 1;
+        if let Some(ch) = self.input[..self.pos].chars().next_back() {
+            self.pos -= ch.len_utf8();
+
+            if ch == '\n' {
+                // only the last read new line column is known, the rest are recalculated
+                match self.newline_column.take() {
+                    Some(column) => {
+                        self.line -= 1;
+                        self.column = column;
+                    }
+                    None => {
+                        let position = self.position_at(self.pos);
+                        self.line = position.line;
+                        self.column = position.column;
+                    }
+                }
+            } else {
+                self.column -= 1;
+            }
         }
     }
 }
@@ -536,9 +951,9 @@ fn is_whitespace_char(ch: char) -> bool {
     ch == ' ' || ch == '\t' || ch == '\n'
 }
 
-// is_letter_char checks if a char is a letter.
+// is_letter_char checks if a char is a (unicode) letter.
 fn is_letter_char(ch: char) -> bool {
-    ch.is_ascii_lowercase() || ch.is_ascii_uppercase()
+    ch.is_alphabetic()
 }
 
 // is_digit_char checks if a char is a digit.
@@ -567,6 +982,17 @@ fn is_sign_start_char(ch: char) -> bool {
     ch == '=' || ch == '?' || ch == '!' || ch == '>' || ch == '<' || ch == '~'
 }
 
+// is_not_char checks if a char is the negation prefix character.
+fn is_not_char(ch: char) -> bool {
+    ch == '!'
+}
+
+// is_negated_sign_char checks if a char could follow the negation
+// prefix in a sign operator (eg. `!=`, `!~`).
+fn is_negated_sign_char(ch: char) -> bool {
+    ch == '=' || ch == '~'
+}
+
 // is_join_start_char checks if a char is a valid join type start character.
 fn is_join_start_char(ch: char) -> bool {
     ch == '&' || ch == '|'
@@ -577,6 +1003,46 @@ fn is_group_start_char(ch: char) -> bool {
     ch == '('
 }
 
+// is_group_end_char checks if a char is a valid group/parenthesis end character.
+fn is_group_end_char(ch: char) -> bool {
+    ch == ')'
+}
+
+// is_bool checks if a literal is a boolean keyword.
+fn is_bool(literal: &str) -> bool {
+    literal == "true" || literal == "false"
+}
+
+// is_null checks if a literal is a null keyword.
+fn is_null(literal: &str) -> bool {
+    literal == "null"
+}
+
+// is_list_start_char checks if a char is a valid list start character.
+fn is_list_start_char(ch: char) -> bool {
+    ch == '['
+}
+
+// is_list_end_char checks if a char is a valid list end character.
+fn is_list_end_char(ch: char) -> bool {
+    ch == ']'
+}
+
+// is_placeholder_start_char checks if a char is a valid named placeholder start character.
+fn is_placeholder_start_char(ch: char) -> bool {
+    ch == '{'
+}
+
+// is_placeholder_end_char checks if a char is a valid named placeholder end character.
+fn is_placeholder_end_char(ch: char) -> bool {
+    ch == '}'
+}
+
+// is_positional_placeholder_char checks if a char is the positional placeholder character.
+fn is_positional_placeholder_char(ch: char) -> bool {
+    ch == '?'
+}
+
 // is_comment_start_char checks if a char is a valid comment start character.
 fn is_comment_start_char(ch: char) -> bool {
     ch == '/'
@@ -610,17 +1076,16 @@ fn is_identifier(literal: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use std::io::BufReader;
+    use std::borrow::Cow;
 
-    use crate::scanner::Token;
+    use crate::{error::ErrorKind, scanner::Token};
 
     use super::Scanner;
 
     #[test]
     fn test_new_scanner() {
-        let s = Scanner::new(BufReader::new("test".as_bytes())).unwrap();
-        let data_bytes = &s.buffer[0..4];
-        let data = std::str::from_utf8(data_bytes).unwrap();
+        let s = Scanner::new("test");
+        let data = &s.input[0..4];
 
         assert!(
             data == "test",
@@ -628,6 +1093,290 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_scanner_scan_borrowed() {
+        struct Scenario {
+            text: &'static str,
+            borrowed: bool,
+        }
+
+        let scenarios = [
+            Scenario {
+                text: "test.a",
+                borrowed: true,
+            },
+            Scenario {
+                text: "-12.5",
+                borrowed: true,
+            },
+            Scenario {
+                text: "'te\"st'",
+                borrowed: true,
+            },
+            Scenario {
+                text: "'te\\'st'",
+                borrowed: false,
+            },
+            Scenario {
+                text: "// test ",
+                borrowed: true,
+            },
+            Scenario {
+                text: "(a = 'b')",
+                borrowed: true,
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let token = Scanner::new(scenario.text)
+                .with_group_literals(true)
+                .scan()
+                .unwrap();
+            let borrowed = match &token {
+                Token::Identifier(value)
+                | Token::Number(value)
+                | Token::Text(value)
+                | Token::Comment(value)
+                | Token::Group(value) => matches!(value, Cow::Borrowed(_)),
+                _ => panic!("({i}) Unexpected token {token}"),
+            };
+
+            assert!(
+                borrowed == scenario.borrowed,
+                "({}) Expected borrowed {}, got {} for {}",
+                i,
+                scenario.borrowed,
+                borrowed,
+                token
+            );
+        }
+    }
+
+    #[test]
+    fn test_new_scanner_invalid_utf8() {
+        let input: &[u8] = b"a = 'Zo\xc3' ||\n b = '\xff'";
+        let err = match Scanner::from_bytes(input) {
+            Ok(_) => panic!("Expected invalid UTF-8 error, got ok"),
+            Err(err) => err,
+        };
+
+        assert!(
+            err.kind == ErrorKind::InvalidUtf8,
+            "Expected invalid UTF-8 error, got {:?}",
+            err.kind
+        );
+        assert!(
+            err.span.start.offset == 7 && err.span.end.offset == 8,
+            "Expected the error span to be 7-8, got {:?}",
+            err.span
+        );
+
+        let input: &[u8] = b"a = 'Zo' ||\n b = '\xff'";
+        let err = match Scanner::from_bytes(input) {
+            Ok(_) => panic!("Expected invalid UTF-8 error, got ok"),
+            Err(err) => err,
+        };
+
+        assert!(
+            err.span.start.line == 2 && err.span.start.column == 7,
+            "Expected the error to start at 2:7, got {}",
+            err.span.start
+        );
+    }
+
+    #[test]
+    fn test_scanner_scan_spanned() {
+        struct Scenario {
+            text: &'static str,
+            // expected start and end (offset, line, column) of each token
+            expects: Vec<[usize; 6]>,
+        }
+
+        let scenarios = [
+            Scenario {
+                text: "",
+                expects: vec![[0, 1, 1, 0, 1, 1]],
+            },
+            Scenario {
+                text: "a > 'b'",
+                expects: vec![
+                    [0, 1, 1, 1, 1, 2],
+                    [1, 1, 2, 2, 1, 3],
+                    [2, 1, 3, 3, 1, 4],
+                    [3, 1, 4, 4, 1, 5],
+                    [4, 1, 5, 7, 1, 8],
+                    [7, 1, 8, 7, 1, 8],
+                ],
+            },
+            Scenario {
+                text: "a\n  // test\n(b)",
+                expects: vec![
+                    [0, 1, 1, 1, 1, 2],
+                    [1, 1, 2, 4, 2, 3],
+                    [4, 2, 3, 12, 3, 1],
+                    [12, 3, 1, 13, 3, 2],
+                    [13, 3, 2, 14, 3, 3],
+                    [14, 3, 3, 15, 3, 4],
+                    [15, 3, 4, 15, 3, 4],
+                ],
+            },
+            Scenario {
+                text: "'日本' = a",
+                expects: vec![
+                    [0, 1, 1, 8, 1, 5],
+                    [8, 1, 5, 9, 1, 6],
+                    [9, 1, 6, 10, 1, 7],
+                    [10, 1, 7, 11, 1, 8],
+                    [11, 1, 8, 12, 1, 9],
+                    [12, 1, 9, 12, 1, 9],
+                ],
+            },
+            Scenario {
+                text: "a\n\n1\n",
+                expects: vec![
+                    [0, 1, 1, 1, 1, 2],
+                    [1, 1, 2, 3, 3, 1],
+                    [3, 3, 1, 4, 3, 2],
+                    [4, 3, 2, 5, 4, 1],
+                    [5, 4, 1, 5, 4, 1],
+                ],
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let mut s = Scanner::new(scenario.text);
+
+            for (j, expect) in scenario.expects.iter().enumerate() {
+                let (token, span) = s.scan_spanned().unwrap();
+                let actual = [
+                    span.start.offset,
+                    span.start.line,
+                    span.start.column,
+                    span.end.offset,
+                    span.end.line,
+                    span.end.column,
+                ];
+
+                assert!(
+                    actual == *expect,
+                    "({}.{}) Expected span {:?}, got {:?} for {}",
+                    i,
+                    j,
+                    expect,
+                    actual,
+                    token
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_scanner_scan_multiline() {
+        // the positions are tracked without rescanning the previous lines
+        let lines = 100_000;
+        let text = "a = 1\n".repeat(lines);
+        let mut s = Scanner::new(&text);
+
+        let mut last = None;
+        loop {
+            let (token, span) = s.scan_spanned().unwrap();
+            if matches!(token, Token::Eof(_)) {
+                break;
+            }
+            last = Some(span);
+        }
+
+        let last = last.unwrap();
+        assert!(
+            last.start.line == lines && last.end.line == lines + 1 && last.end.column == 1,
+            "Unexpected last token span {:?}",
+            last
+        );
+    }
+
+    #[test]
+    fn test_scanner_scan_groups() {
+        struct Scenario {
+            text: &'static str,
+            expects: Vec<&'static str>,
+        }
+
+        let scenarios = [
+            Scenario {
+                text: "a)",
+                expects: vec!["{identifier a}", "{group_end )}"],
+            },
+            Scenario {
+                text: "(a b",
+                expects: vec![
+                    "{group_start (}",
+                    "{identifier a}",
+                    "{whitespace  }",
+                    "{identifier b}",
+                ],
+            },
+            Scenario {
+                text: "!(a != b) && !!c !~ 'd'",
+                expects: vec![
+                    "{not !}",
+                    "{group_start (}",
+                    "{identifier a}",
+                    "{whitespace  }",
+                    "{sign !=}",
+                    "{whitespace  }",
+                    "{identifier b}",
+                    "{group_end )}",
+                    "{whitespace  }",
+                    "{join &&}",
+                    "{whitespace  }",
+                    "{not !}",
+                    "{not !}",
+                    "{identifier c}",
+                    "{whitespace  }",
+                    "{sign !~}",
+                    "{whitespace  }",
+                    "{text d}",
+                ],
+            },
+            Scenario {
+                text: "((a)')')",
+                expects: vec![
+                    "{group_start (}",
+                    "{group_start (}",
+                    "{identifier a}",
+                    "{group_end )}",
+                    "{text )}",
+                    "{group_end )}",
+                ],
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let mut s = Scanner::new(scenario.text);
+
+            for (j, expect) in scenario.expects.iter().enumerate() {
+                let token_print = s.scan().unwrap().to_string();
+
+                assert!(
+                    token_print == *expect,
+                    "({}.{}) Expected token {}, got {}",
+                    i,
+                    j,
+                    expect,
+                    token_print
+                );
+            }
+
+            let last_token = s.scan().unwrap();
+            assert!(
+                matches!(last_token, Token::Eof(_)),
+                "({}) Expected EOF token, got {}",
+                i,
+                last_token
+            );
+        }
+    }
+
     #[test]
     fn test_scanner_scan() {
         struct Output {
@@ -721,6 +1470,13 @@ mod tests {
                     },
                 ],
             },
+            TestScenario {
+                text: r"名前.ünïcode",
+                expects: vec![Output {
+                    error: false,
+                    print: r"{identifier 名前.ünïcode}",
+                }],
+            },
             TestScenario {
                 text: r"test#@",
                 expects: vec![Output {
@@ -889,6 +1645,27 @@ mod tests {
                     print: r#"{text tes@#,;!@#%^'"t}"#,
                 }],
             },
+            TestScenario {
+                text: r"'Zoë'",
+                expects: vec![Output {
+                    error: false,
+                    print: r"{text Zoë}",
+                }],
+            },
+            TestScenario {
+                text: r#""日本 \"語\"""#,
+                expects: vec![Output {
+                    error: false,
+                    print: r#"{text 日本 "語"}"#,
+                }],
+            },
+            TestScenario {
+                text: r"'😀",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{text '😀}",
+                }],
+            },
             TestScenario {
                 text: r#""test"#,
                 expects: vec![Output {
@@ -903,6 +1680,179 @@ mod tests {
                     print: r"{text 'test}",
                 }],
             },
+            // lists
+            TestScenario {
+                text: r"[]",
+                expects: vec![Output {
+                    error: false,
+                    print: r"{list []}",
+                }],
+            },
+            TestScenario {
+                text: r"['a', 'b\'c' ,-1.5,2]",
+                expects: vec![Output {
+                    error: false,
+                    print: r"{list [{text a} {text b'c} {number -1.5} {number 2}]}",
+                }],
+            },
+            TestScenario {
+                text: "[\n  '日本',\n  \"x]\"\n]",
+                expects: vec![Output {
+                    error: false,
+                    print: r"{list [{text 日本} {text x]}]}",
+                }],
+            },
+            TestScenario {
+                text: r"['a',]",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{list [{text a}]}",
+                }],
+            },
+            TestScenario {
+                text: r"[,]",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{list []}",
+                }],
+            },
+            TestScenario {
+                text: r"[1 2]",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{list [{number 1}]}",
+                }],
+            },
+            TestScenario {
+                text: r"[a]",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{list []}",
+                }],
+            },
+            TestScenario {
+                text: r"['a'",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{list [{text a}]}",
+                }],
+            },
+            TestScenario {
+                text: r"[1.]",
+                expects: vec![
+                    Output {
+                        error: true,
+                        print: r"{number 1.}",
+                    },
+                    Output {
+                        error: true,
+                        print: r"{unexpected ]}",
+                    },
+                ],
+            },
+            TestScenario {
+                text: r"[true,false , null]",
+                expects: vec![Output {
+                    error: false,
+                    print: r"{list [{bool true} {bool false} {null null}]}",
+                }],
+            },
+            TestScenario {
+                text: r"[nil]",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{list []}",
+                }],
+            },
+            // bool and null
+            TestScenario {
+                text: r"true false null",
+                expects: vec![
+                    Output {
+                        error: false,
+                        print: r"{bool true}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{whitespace  }",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{bool false}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{whitespace  }",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{null null}",
+                    },
+                ],
+            },
+            TestScenario {
+                text: r"True nulls false.a",
+                expects: vec![
+                    Output {
+                        error: false,
+                        print: r"{identifier True}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{whitespace  }",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{identifier nulls}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{whitespace  }",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{identifier false.a}",
+                    },
+                ],
+            },
+            // membership operators
+            TestScenario {
+                text: r"in !in inner !inner",
+                expects: vec![
+                    Output {
+                        error: false,
+                        print: r"{sign in}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{whitespace  }",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{sign !in}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{whitespace  }",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{identifier inner}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{whitespace  }",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{not !}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{identifier inner}",
+                    },
+                ],
+            },
             // join types
             TestScenario {
                 text: r"&&||",
@@ -1144,6 +2094,106 @@ mod tests {
                     print: r#"{group "ab)(c)}"#,
                 }],
             },
+            // placeholders
+            TestScenario {
+                text: r"{:userId}",
+                expects: vec![Output {
+                    error: false,
+                    print: r"{placeholder {:userId}}",
+                }],
+            },
+            TestScenario {
+                text: r"{:_a1}?",
+                expects: vec![
+                    Output {
+                        error: false,
+                        print: r"{placeholder {:_a1}}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{placeholder ?}",
+                    },
+                ],
+            },
+            TestScenario {
+                text: r"a=?",
+                expects: vec![
+                    Output {
+                        error: false,
+                        print: r"{identifier a}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{sign =}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{placeholder ?}",
+                    },
+                ],
+            },
+            TestScenario {
+                text: r"a?!=?",
+                expects: vec![
+                    Output {
+                        error: false,
+                        print: r"{identifier a}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{sign ?!=}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{placeholder ?}",
+                    },
+                ],
+            },
+            TestScenario {
+                text: r"{:}",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{placeholder {:}}",
+                }],
+            },
+            TestScenario {
+                text: r"{a} 1",
+                expects: vec![
+                    Output {
+                        error: true,
+                        print: r"{placeholder {a}}",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{whitespace  }",
+                    },
+                    Output {
+                        error: false,
+                        print: r"{number 1}",
+                    },
+                ],
+            },
+            TestScenario {
+                text: r"{:1a}",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{placeholder {:1a}}",
+                }],
+            },
+            TestScenario {
+                text: r"{:a b}",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{placeholder {:a b}}",
+                }],
+            },
+            TestScenario {
+                text: r"{:a",
+                expects: vec![Output {
+                    error: true,
+                    print: r"{placeholder {:a}",
+                }],
+            },
             // comments
             TestScenario {
                 text: r"/ test",
@@ -1203,6 +2253,13 @@ mod tests {
                     print: r"{comment test1 //test2}",
                 }],
             },
+            TestScenario {
+                text: r"// héllo 世界",
+                expects: vec![Output {
+                    error: false,
+                    print: r"{comment héllo 世界}",
+                }],
+            },
             TestScenario {
                 text: r"///test",
                 expects: vec![Output {
@@ -1213,7 +2270,7 @@ mod tests {
         ];
 
         for (i, scenario) in test_scenarios.iter().enumerate() {
-            let mut s = Scanner::new(BufReader::new(scenario.text.as_bytes())).unwrap();
+            let mut s = Scanner::new(scenario.text).with_group_literals(true);
 
             // scan the text tokens
             for (j, expect) in scenario.expects.iter().enumerate() {
@@ -0,0 +1,557 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    error::{Error, ErrorKind},
+    parser::{Expr, ExprGroupItem, ExprGroups},
+    scanner::{SignOp, Token},
+};
+
+// FieldType represents the type of a schema field.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    String,
+    Number,
+    Bool,
+    // DateTime is a text field with `YYYY-MM-DD[ HH:MM[:SS[.fff]][Z|+HH:MM]]` formatted values
+    // (`T` could be also used as date and time separator).
+    DateTime,
+    Array(Box<FieldType>),
+}
+
+impl FieldType {
+    // array returns an array field type with the provided element type.
+    pub fn array(element: FieldType) -> Self {
+        FieldType::Array(Box::new(element))
+    }
+
+    // default_ops returns the sign operators allowed for the type by default.
+    fn default_ops(&self) -> Vec<SignOp> {
+        match self {
+            FieldType::String => vec![
+                SignOp::Eq,
+                SignOp::Neq,
+                SignOp::Like,
+                SignOp::Nlike,
+                SignOp::Lt,
+                SignOp::Lte,
+                SignOp::Gt,
+                SignOp::Gte,
+                SignOp::In,
+                SignOp::Nin,
+            ],
+            FieldType::Number | FieldType::DateTime => vec![
+                SignOp::Eq,
+                SignOp::Neq,
+                SignOp::Lt,
+                SignOp::Lte,
+                SignOp::Gt,
+                SignOp::Gte,
+                SignOp::In,
+                SignOp::Nin,
+            ],
+            FieldType::Bool => vec![SignOp::Eq, SignOp::Neq, SignOp::In, SignOp::Nin],
+            FieldType::Array(element) => element
+                .default_ops()
+                .into_iter()
+                .filter_map(array_op)
+                .collect(),
+        }
+    }
+
+    // expected returns the token kinds of the literals matching the type.
+    fn expected(&self) -> &'static [&'static str] {
+        match self {
+            FieldType::String | FieldType::DateTime => &["text"],
+            FieldType::Number => &["number"],
+            FieldType::Bool => &["bool"],
+            FieldType::Array(_) => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SchemaField {
+    kind: FieldType,
+    ops: Vec<SignOp>,
+}
+
+// Schema represents the set of identifiers allowed in a filter
+// together with their types and allowed sign operators.
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    fields: HashMap<String, SchemaField>,
+}
+
+impl Schema {
+    // new creates a new empty schema (aka. no identifiers are allowed).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // with_field returns the schema with the field allowing the default
+    // sign operators of its type:
+    //   - string - all non-array operators
+    //   - number and datetime - all non-array operators except `~` and `!~`
+    //   - bool - `=`, `!=`, `in` and `!in`
+    //   - array - the `?` operators of its element type (eg. `?=`, `?~`)
+    pub fn with_field(self, name: &str, kind: FieldType) -> Self {
+        let ops = kind.default_ops();
+        self.with_field_ops(name, kind, &ops)
+    }
+
+    // with_field_ops returns the schema with the field allowing only the provided sign operators.
+    pub fn with_field_ops(mut self, name: &str, kind: FieldType, ops: &[SignOp]) -> Self {
+        self.fields.insert(
+            name.to_string(),
+            SchemaField {
+                kind,
+                ops: ops.to_vec(),
+            },
+        );
+        self
+    }
+
+    // field_type returns the type of the field (if it exists).
+    pub fn field_type(&self, name: &str) -> Option<&FieldType> {
+        self.fields.get(name).map(|field| &field.kind)
+    }
+
+    // allows checks if the sign operator is allowed for the field.
+    pub fn allows(&self, name: &str, op: SignOp) -> bool {
+        self.fields
+            .get(name)
+            .is_some_and(|field| field.ops.contains(&op))
+    }
+}
+
+// validate checks the AST against the schema and returns all found violations:
+//   - `UnknownIdentifier` - the identifier is not in the schema
+//   - `NotAllowedSign` - the sign operator is not allowed for the identifier
+//   - `TypeMismatch` - the other operand (or list item) doesn't match the identifier type
+//   - `InvalidDatetime` - the text operand of a datetime identifier is not a valid datetime
+//
+// `null` is allowed only with the `=`, `!=`, `?=`, `?!=`, `in` and `!in` operators.
+// The `?` operators compare the array field elements, all other operators the field itself.
+//
+// The errors are reported in the order of the expressions and each error span
+// is the location of the whole expression.
+pub fn validate(expr_groups: &ExprGroups, schema: &Schema) -> Result<(), Vec<Error>> {
+    let mut errors = Vec::new();
+
+    validate_groups(expr_groups, schema, &mut errors);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn validate_groups(expr_groups: &ExprGroups, schema: &Schema, errors: &mut Vec<Error>) {
+    for expr_group in expr_groups {
+        validate_item(&expr_group.item, schema, errors);
+    }
+}
+
+fn validate_item(item: &ExprGroupItem, schema: &Schema, errors: &mut Vec<Error>) {
+    match item {
+        ExprGroupItem::Expr(expr) => validate_expr(expr, schema, errors),
+        ExprGroupItem::ExprGroups(expr_groups) => validate_groups(expr_groups, schema, errors),
+        ExprGroupItem::Not(item) => validate_item(item, schema, errors),
+    }
+}
+
+fn validate_expr(expr: &Expr, schema: &Schema, errors: &mut Vec<Error>) {
+    let left = field(&expr.left, schema, expr, errors);
+    let right = field(&expr.right, schema, expr, errors);
+
+    let (name, kind, op, other) = match (&expr.left, left, right) {
+        // unknown identifiers are already reported
+        (Token::Identifier(_), None, _) => return,
+        (Token::Identifier(name), Some(kind), _) => (name, kind, expr.op, &expr.right),
+        // the identifier is on the right side of a literal
+        (_, _, Some(kind)) => match &expr.right {
+            Token::Identifier(name) => (name, kind, flip(expr.op), &expr.left),
+            _ => return,
+        },
+        _ => return,
+    };
+
+    if !schema.allows(name, op) {
+        errors.push(Error::new(
+            ErrorKind::NotAllowedSign,
+            Token::Sign(expr.op.to_string().into()),
+            expr.span,
+        ));
+        return;
+    }
+
+    check_operand(
+        kind,
+        op,
+        other,
+        &|name| schema.field_type(name).cloned(),
+        expr,
+        errors,
+    );
+}
+
+// check_types checks the other operand of an expression with a typed identifier
+// against the identifier type (the identifiers without a type are not checked)
+// and returns the first found `TypeMismatch` error.
+pub(crate) fn check_types(
+    expr: &Expr,
+    field_type: &dyn Fn(&str) -> Option<FieldType>,
+) -> Result<(), Error> {
+    let typed = |token: &Token| match token {
+        Token::Identifier(name) => field_type(name),
+        _ => None,
+    };
+
+    let (kind, op, other) = match (typed(&expr.left), typed(&expr.right)) {
+        (Some(kind), _) => (kind, expr.op, &expr.right),
+        (None, Some(kind)) => (kind, flip(expr.op), &expr.left),
+        (None, None) => return Ok(()),
+    };
+
+    let mut errors = Vec::new();
+    check_operand(&kind, op, other, field_type, expr, &mut errors);
+
+    errors.into_iter().next().map_or(Ok(()), Err)
+}
+
+// check_operand checks if the other operand (or its list items) matches the identifier type.
+fn check_operand(
+    kind: &FieldType,
+    op: SignOp,
+    other: &Token,
+    field_type: &dyn Fn(&str) -> Option<FieldType>,
+    expr: &Expr,
+    errors: &mut Vec<Error>,
+) {
+    // the `?` operators compare the array elements
+    let kind = match kind {
+        FieldType::Array(element) if is_any(op) => element.as_ref(),
+        kind => kind,
+    };
+
+    match other {
+        Token::Identifier(other_name) => match field_type(other_name) {
+            Some(other_kind) if &other_kind != kind => errors.push(
+                Error::new(ErrorKind::TypeMismatch, other.clone(), expr.span)
+                    .with_expected(kind.expected()),
+            ),
+            _ => (),
+        },
+        Token::List(items) => {
+            for item in items {
+                check_literal(item, kind, op, expr, errors);
+            }
+        }
+        literal => check_literal(literal, kind, op, expr, errors),
+    }
+}
+
+// field resolves the type of an identifier token (reporting the unknown ones).
+fn field<'s>(
+    token: &Token,
+    schema: &'s Schema,
+    expr: &Expr,
+    errors: &mut Vec<Error>,
+) -> Option<&'s FieldType> {
+    match token {
+        Token::Identifier(name) => {
+            let kind = schema.field_type(name);
+            if kind.is_none() {
+                errors.push(Error::new(
+                    ErrorKind::UnknownIdentifier,
+                    token.clone(),
+                    expr.span,
+                ));
+            }
+            kind
+        }
+        _ => None,
+    }
+}
+
+// check_literal checks if the literal token matches the type.
+fn check_literal(
+    token: &Token,
+    kind: &FieldType,
+    op: SignOp,
+    expr: &Expr,
+    errors: &mut Vec<Error>,
+) {
+    let valid = match (token, kind) {
+        (Token::Null(_), _) => matches!(
+            op,
+            SignOp::Eq | SignOp::Neq | SignOp::AnyEq | SignOp::AnyNeq | SignOp::In | SignOp::Nin
+        ),
+        (Token::Text(value), FieldType::DateTime) => {
+            if !is_datetime(value) {
+                errors.push(Error::new(
+                    ErrorKind::InvalidDatetime,
+                    token.clone(),
+                    expr.span,
+                ));
+            }
+            true
+        }
+        (Token::Text(_), FieldType::String)
+        | (Token::Number(_), FieldType::Number)
+        | (Token::Bool(_), FieldType::Bool) => true,
+        _ => false,
+    };
+
+    if !valid {
+        errors.push(
+            Error::new(ErrorKind::TypeMismatch, token.clone(), expr.span)
+                .with_expected(kind.expected()),
+        );
+    }
+}
+
+// array_op returns the `?` variant of a sign operator (if there is one).
+fn array_op(op: SignOp) -> Option<SignOp> {
+    match op {
+        SignOp::Eq => Some(SignOp::AnyEq),
+        SignOp::Neq => Some(SignOp::AnyNeq),
+        SignOp::Like => Some(SignOp::AnyLike),
+        SignOp::Nlike => Some(SignOp::AnyNlike),
+        SignOp::Lt => Some(SignOp::AnyLt),
+        SignOp::Lte => Some(SignOp::AnyLte),
+        SignOp::Gt => Some(SignOp::AnyGt),
+        SignOp::Gte => Some(SignOp::AnyGte),
+        _ => None,
+    }
+}
+
+// is_any checks if the sign operator is one of the `?` array operators.
+fn is_any(op: SignOp) -> bool {
+    matches!(
+        op,
+        SignOp::AnyEq
+            | SignOp::AnyNeq
+            | SignOp::AnyLike
+            | SignOp::AnyNlike
+            | SignOp::AnyLt
+            | SignOp::AnyLte
+            | SignOp::AnyGt
+            | SignOp::AnyGte
+    )
+}
+
+// flip returns the operator with swapped operands.
+pub(crate) fn flip(op: SignOp) -> SignOp {
+    match op {
+        SignOp::Lt => SignOp::Gt,
+        SignOp::Lte => SignOp::Gte,
+        SignOp::Gt => SignOp::Lt,
+        SignOp::Gte => SignOp::Lte,
+        op => op,
+    }
+}
+
+// is_datetime checks if the text is a valid datetime literal
+// (including the day of month, eg. `2023-02-29` is not valid).
+//
+// The `%` checks are kept instead of `is_multiple_of` (stable since Rust 1.87).
+#[allow(clippy::manual_is_multiple_of)]
+fn is_datetime(value: &str) -> bool {
+    static DATETIME_REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"^([0-9]{4})-(0[1-9]|1[0-2])-(0[1-9]|[12][0-9]|3[01])([ T]([01][0-9]|2[0-3]):[0-5][0-9](:[0-5][0-9](\.[0-9]+)?)?(Z|[+-]([01][0-9]|2[0-3]):?[0-5][0-9])?)?$",
+        )
+        .unwrap()
+    });
+
+    let captures = match DATETIME_REGEX.captures(value) {
+        Some(captures) => captures,
+        None => return false,
+    };
+
+    // the groups are already validated as numbers by the regex
+    let year: u32 = captures[1].parse().unwrap();
+    let month: u32 = captures[2].parse().unwrap();
+    let day: u32 = captures[3].parse().unwrap();
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+
+    day <= days
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::ErrorKind,
+        parser::parse,
+        scanner::SignOp,
+        schema::{is_datetime, validate, FieldType, Schema},
+    };
+
+    #[test]
+    fn test_validate() {
+        struct Scenario {
+            input: &'static str,
+            expected: Vec<(ErrorKind, &'static str)>,
+        }
+
+        let schema = Schema::new()
+            .with_field("title", FieldType::String)
+            .with_field("views", FieldType::Number)
+            .with_field("likes", FieldType::Number)
+            .with_field("active", FieldType::Bool)
+            .with_field("created", FieldType::DateTime)
+            .with_field("tags", FieldType::array(FieldType::String))
+            .with_field_ops("status", FieldType::String, &[SignOp::Eq, SignOp::In]);
+
+        let scenarios = [
+            Scenario {
+                input: r"title ~ 'x' && views > 1 && active = true && created >= '2024-01-02 10:00:00Z'",
+                expected: vec![],
+            },
+            Scenario {
+                input: r"tags ?= 'x' && tags ?~ 'y' && status in ['a', null] && title = null && 1 < views && views = likes",
+                expected: vec![],
+            },
+            Scenario {
+                input: r"password_hash = 'x' || title = unknown",
+                expected: vec![
+                    (ErrorKind::UnknownIdentifier, "password_hash"),
+                    (ErrorKind::UnknownIdentifier, "unknown"),
+                ],
+            },
+            Scenario {
+                input: r"created > 'banana' && views = '1' && active = 1 && title = 1",
+                expected: vec![
+                    (ErrorKind::InvalidDatetime, "banana"),
+                    (ErrorKind::TypeMismatch, "1"),
+                    (ErrorKind::TypeMismatch, "1"),
+                    (ErrorKind::TypeMismatch, "1"),
+                ],
+            },
+            Scenario {
+                input: r"created in ['2024-02-29', '2023-02-29', 1] || created < '2024-01-02 1٠:00'",
+                expected: vec![
+                    (ErrorKind::InvalidDatetime, "2023-02-29"),
+                    (ErrorKind::TypeMismatch, "1"),
+                    (ErrorKind::InvalidDatetime, "2024-01-02 1٠:00"),
+                ],
+            },
+            Scenario {
+                input: r"(status != 'a' || views ~ 1) && !(active > false) && tags = 'x' && title ?= 'x'",
+                expected: vec![
+                    (ErrorKind::NotAllowedSign, "!="),
+                    (ErrorKind::NotAllowedSign, "~"),
+                    (ErrorKind::NotAllowedSign, ">"),
+                    (ErrorKind::NotAllowedSign, "="),
+                    (ErrorKind::NotAllowedSign, "?="),
+                ],
+            },
+            Scenario {
+                input: r"views in [1, 'x', null] && views > null && tags ?= 1 && title = views && 'x' > views",
+                expected: vec![
+                    (ErrorKind::TypeMismatch, "x"),
+                    (ErrorKind::TypeMismatch, "null"),
+                    (ErrorKind::TypeMismatch, "1"),
+                    (ErrorKind::TypeMismatch, "views"),
+                    (ErrorKind::TypeMismatch, "x"),
+                ],
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let errors = match validate(&parse(scenario.input).unwrap(), &schema) {
+                Ok(_) => vec![],
+                Err(errors) => errors,
+            };
+            let result: Vec<_> = errors
+                .iter()
+                .map(|err| (err.kind, err.token.literal()))
+                .collect();
+
+            assert!(
+                result == scenario.expected,
+                "({}) Expected {:?}, got {:?}",
+                i,
+                scenario.expected,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_span() {
+        let schema = Schema::new().with_field("a", FieldType::Number);
+
+        let errors = validate(&parse("a = 1 && b = 2").unwrap(), &schema).unwrap_err();
+
+        assert!(errors.len() == 1, "Expected 1 error, got {:?}", errors);
+        assert!(
+            errors[0].to_string() == "unknown or not allowed identifier `b` (identifier) at 1:10",
+            "Unexpected error {}",
+            errors[0]
+        );
+
+        let schema = Schema::new().with_field("created", FieldType::DateTime);
+
+        let errors = validate(&parse("created = 1").unwrap(), &schema).unwrap_err();
+
+        assert!(
+            errors[0].to_string()
+                == "operand type doesn't match the identifier type `1` (number) at 1:1, expected text",
+            "Unexpected error {}",
+            errors[0]
+        );
+    }
+
+    #[test]
+    fn test_is_datetime() {
+        let scenarios = [
+            ("2024-01-02", true),
+            ("2024-01-02 10:20", true),
+            ("2024-01-02T10:20:30", true),
+            ("2024-01-02 10:20:30.123Z", true),
+            ("2024-01-02T10:20:30+02:00", true),
+            ("2024-01-02T10:20:30-0130", true),
+            ("", false),
+            ("banana", false),
+            ("2024-13-02", false),
+            ("2024-01-32", false),
+            ("2024-01-02 24:00", false),
+            ("2024-01-02 10:20:30 Z", false),
+            ("24-01-02", false),
+            ("2024-02-29", true),
+            ("2000-02-29", true),
+            ("2023-02-29", false),
+            ("1900-02-29", false),
+            ("2024-02-31", false),
+            ("2024-04-31", false),
+            ("2024-12-31", true),
+            ("٢٠٢٤-01-02", false),
+            ("2024-01-02 1٠:20", false),
+        ];
+
+        for (i, (value, expected)) in scenarios.iter().enumerate() {
+            let result = is_datetime(value);
+            assert!(
+                result == *expected,
+                "({}) Expected {} for {}, got {}",
+                i,
+                expected,
+                value,
+                result
+            );
+        }
+    }
+}
@@ -0,0 +1,245 @@
+// Manual serde implementations for the AST types that couldn't be derived
+// (the derived ones are declared next to the type definitions).
+//
+// JSON shape:
+//
+//   - `ExprGroups` is an array of `ExprGroup` objects
+//   - `ExprGroup` is an object with a `join` key (`"and"` or `"or"`)
+//     and the keys of its item:
+//       - expression: `left`, `op` and `right`
+//       - nested groups: `groups` (`ExprGroups` array)
+//       - negation: `not` (an item object without `join`)
+//   - `SignOp` is its operator string (eg. `">"`, `"?!~"`, `"in"`)
+//   - `Token` is an object with a single key - the token kind -
+//     and its literal (or items array for lists) as value
+//     (`null` for `Token::None`, eg. `{"none":null}`)
+//
+// For example `id > 1 || !(a in ['b'])` is represented as:
+//
+//   [
+//     {"join":"and","left":{"identifier":"id"},"op":">","right":{"number":"1"}},
+//     {"join":"or","not":{"groups":[
+//       {"join":"and","left":{"identifier":"a"},"op":"in","right":{"list":[{"text":"b"}]}}
+//     ]}}
+//   ]
+//
+// Spans are not serialized.
+
+use std::borrow::Cow;
+
+use serde::{de, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    parser::{Expr, ExprGroupItem, ExprGroups},
+    scanner::{SignOp, Token},
+};
+
+impl Serialize for SignOp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SignOp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Cow::<str>::deserialize(deserializer)?;
+
+        SignOp::from_str(&value)
+            .ok_or_else(|| de::Error::custom(format!("invalid sign operator `{value}`")))
+    }
+}
+
+impl Serialize for ExprGroupItem<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ExprGroupItem::Expr(expr) => {
+                let mut map = serializer.serialize_map(Some(3))?;
+                map.serialize_entry("left", &expr.left)?;
+                map.serialize_entry("op", &expr.op)?;
+                map.serialize_entry("right", &expr.right)?;
+                map.end()
+            }
+            ExprGroupItem::ExprGroups(expr_groups) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("groups", expr_groups)?;
+                map.end()
+            }
+            ExprGroupItem::Not(item) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("not", item)?;
+                map.end()
+            }
+        }
+    }
+}
+
+// serialize_none serializes the `Token::None` value as `null`
+// (so that the token is still a single key object).
+pub(crate) fn serialize_none<S: Serializer>(serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_unit()
+}
+
+// deserialize_none deserializes the `null` value of `Token::None`.
+pub(crate) fn deserialize_none<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(), D::Error> {
+    <()>::deserialize(deserializer)
+}
+
+// ItemRepr is the intermediate deserialization form of `ExprGroupItem`
+// (the group `join` key is consumed by `ExprGroup` and any other key is an error).
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ItemRepr<'a> {
+    left: Option<Token<'a>>,
+    op: Option<SignOp>,
+    right: Option<Token<'a>>,
+    groups: Option<ExprGroups<'a>>,
+    not: Option<Box<ExprGroupItem<'a>>>,
+}
+
+impl<'de, 'a> Deserialize<'de> for ExprGroupItem<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ItemRepr::deserialize(deserializer)?;
+
+        match repr {
+            ItemRepr {
+                left: Some(left),
+                op: Some(op),
+                right: Some(right),
+                groups: None,
+                not: None,
+            } => Ok(ExprGroupItem::Expr(Expr::new(left, op, right))),
+            ItemRepr {
+                left: None,
+                op: None,
+                right: None,
+                groups: Some(expr_groups),
+                not: None,
+            } => Ok(ExprGroupItem::ExprGroups(expr_groups)),
+            ItemRepr {
+                left: None,
+                op: None,
+                right: None,
+                groups: None,
+                not: Some(item),
+            } => Ok(ExprGroupItem::Not(item)),
+            _ => Err(de::Error::custom(
+                "expected either `left`, `op` and `right`, `groups` or `not` keys",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::{parse, ExprGroups},
+        scanner::{JoinOp, SignOp, Token},
+    };
+
+    #[test]
+    fn test_serialize() {
+        struct Scenario {
+            input: &'static str,
+            expected: &'static str,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"id > 1",
+                expected: r#"[{"join":"and","left":{"identifier":"id"},"op":">","right":{"number":"1"}}]"#,
+            },
+            Scenario {
+                input: r"a ?!~ 'b\'c' || d = true && e != null",
+                expected: r#"[{"join":"and","left":{"identifier":"a"},"op":"?!~","right":{"text":"b'c"}},{"join":"or","left":{"identifier":"d"},"op":"=","right":{"bool":"true"}},{"join":"and","left":{"identifier":"e"},"op":"!=","right":{"null":"null"}}]"#,
+            },
+            Scenario {
+                input: r"(a in ['b', 1]) || !c = 1",
+                expected: r#"[{"join":"and","groups":[{"join":"and","left":{"identifier":"a"},"op":"in","right":{"list":[{"text":"b"},{"number":"1"}]}}]},{"join":"or","not":{"left":{"identifier":"c"},"op":"=","right":{"number":"1"}}}]"#,
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let ast = parse(scenario.input).unwrap();
+            let json = serde_json::to_string(&ast).unwrap();
+
+            assert!(
+                json == scenario.expected,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected,
+                json
+            );
+
+            let deserialized: ExprGroups = serde_json::from_str(&json).unwrap();
+            assert!(
+                deserialized == ast,
+                "({}) Expected {}, got {}",
+                i,
+                ast,
+                deserialized
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let json = r#"[
+            {"join": "and", "not": {"groups": [
+                {"left": {"identifier": "a"}, "op": "!in", "right": {"list": []}, "join": "and"},
+                {"join": "or", "left": {"text": "b"}, "op": "?<=", "right": {"number": "-1.5"}}
+            ]}}
+        ]"#;
+
+        let ast: ExprGroups = serde_json::from_str(json).unwrap();
+        let expected = parse(r"!(a !in [] || 'b' ?<= -1.5)").unwrap();
+
+        assert!(ast == expected, "Expected {}, got {}", expected, ast);
+        assert!(
+            ast[0].join == JoinOp::And,
+            "Expected && join, got {}",
+            ast[0].join
+        );
+
+        let token: Token = serde_json::from_str(r#"{"whitespace":" "}"#).unwrap();
+        assert!(
+            token == Token::Ws(" ".into()),
+            "Expected whitespace token, got {}",
+            token
+        );
+
+        let json = serde_json::to_string(&Token::None).unwrap();
+        assert!(
+            json == r#"{"none":null}"#,
+            "Expected none object, got {}",
+            json
+        );
+
+        let token: Token = serde_json::from_str(&json).unwrap();
+        assert!(token == Token::None, "Expected none token, got {}", token);
+
+        let op: SignOp = serde_json::from_str(r#""?~""#).unwrap();
+        assert!(op == SignOp::AnyLike, "Expected ?~, got {}", op);
+    }
+
+    #[test]
+    fn test_deserialize_errors() {
+        let scenarios = [
+            r#"{"join":"and"}"#,
+            r#"{"join":"xor","left":{"identifier":"a"},"op":"=","right":{"number":"1"}}"#,
+            r#"{"join":"and","left":{"identifier":"a"},"op":"=="}"#,
+            r#"{"join":"and","left":{"identifier":"a"},"op":"==","right":{"number":"1"}}"#,
+            r#"{"join":"and","left":{"ident":"a"},"op":"=","right":{"number":"1"}}"#,
+            r#"{"join":"and","left":{"identifier":"a"},"op":"=","right":{"number":"1"},"not":{"groups":[]}}"#,
+            r#"{"join":"and","left":{"identifier":"a"},"op":"=","rigth":{"number":"1"}}"#,
+            r#"{"join":"and","not":{"join":"and","left":{"identifier":"a"},"op":"=","right":{"number":"1"}}}"#,
+            r#"{"join":"and","left":"none","op":"=","right":{"number":"1"}}"#,
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let json = format!("[{scenario}]");
+            let result = serde_json::from_str::<ExprGroups>(&json);
+
+            assert!(result.is_err(), "({}) Expected error, got {:?}", i, result);
+        }
+    }
+}
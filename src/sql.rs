@@ -0,0 +1,561 @@
+mod dialect;
+
+pub use dialect::Dialect;
+pub use dialect::MySql;
+pub use dialect::Postgres;
+pub use dialect::Sqlite;
+
+use crate::{
+    error::{Error, ErrorKind},
+    parser::{Expr, ExprGroupItem, ExprGroups},
+    scanner::{JoinOp, SignOp, Token},
+    span::Span,
+};
+
+// Value represents a single bound SQL parameter value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+// Fragment represents a compiled SQL WHERE clause fragment
+// (without the `WHERE` keyword) and its positional bound parameters.
+//
+// The N-th parameter corresponds to the N-th placeholder in the fragment
+// (aka. `$N` for PostgreSQL).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+// compile compiles the provided AST into a PostgreSQL WHERE clause fragment
+// (see `compile_with` for the other dialects).
+//
+// Every identifier is resolved through the `columns` mapping which returns
+// the column name for the identifier or `None` if the identifier is not allowed.
+// The returned column name is always quoted (see `quote_identifier`).
+//
+// All text, number and bool literals are bound as parameters.
+//
+// The sign operators are compiled as follows (`a` and `b` are the left and
+// the right operands and `c` is an array column):
+//   - `a = b`, `a != b`, `a < b`, `a <= b`, `a > b`, `a >= b` - `=`, `<>`, `<`, `<=`, `>`, `>=`
+//   - `a = null`, `a != null` - `a IS NULL`, `a IS NOT NULL`
+//   - `a ~ b`, `a !~ b` - `a ILIKE b ESCAPE '\'` (and `NOT ILIKE`) where the
+//     wildcards of a literal `b` are escaped and the value is wrapped with `%`
+//   - `a in [..]`, `a !in [..]` - `a IN (..)`, `a NOT IN (..)` (the null list items
+//     are compiled into `OR a IS NULL` and `AND a IS NOT NULL`)
+//   - `c ?= b`, `c ?!= b`, `c ?< b`, ... - `b = ANY(c)`, `b <> ANY(c)`, `b > ANY(c)`, ...
+//   - `c ?~ b`, `c ?!~ b` - `EXISTS (SELECT 1 FROM unnest(c) AS _v WHERE _v ILIKE b ESCAPE '\')`
+pub fn compile<F>(expr_groups: &ExprGroups, columns: F) -> Result<Fragment, Error>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    compile_with(expr_groups, &Postgres, columns)
+}
+
+// compile_with compiles the provided AST into a WHERE clause fragment
+// for the specified SQL dialect.
+//
+// The compiled operators are the same as in `compile`, except that
+// the identifiers quoting, the placeholders, the like expressions and
+// the `Any*` operators are generated by the dialect.
+pub fn compile_with<D, F>(
+    expr_groups: &ExprGroups,
+    dialect: &D,
+    columns: F,
+) -> Result<Fragment, Error>
+where
+    D: Dialect + ?Sized,
+    F: Fn(&str) -> Option<String>,
+{
+    let mut compiler = Compiler {
+        dialect,
+        columns,
+        sql: String::new(),
+        params: Vec::new(),
+    };
+
+    compiler.write_groups(expr_groups, Span::default())?;
+
+    Ok(Fragment {
+        sql: compiler.sql,
+        params: compiler.params,
+    })
+}
+
+// quote_identifier quotes each dot separated part of the provided
+// column name (eg. `author.name` -> `"author"."name"`).
+pub fn quote_identifier(name: &str) -> String {
+    Postgres.quote_identifier(name)
+}
+
+// escape_like escapes the `LIKE` wildcard and escape characters.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Operand represents a compiled expression operand.
+enum Operand {
+    Sql(String),
+    Null,
+}
+
+struct Compiler<'d, D: ?Sized, F> {
+    dialect: &'d D,
+    columns: F,
+    sql: String,
+    params: Vec<Value>,
+}
+
+impl<D, F> Compiler<'_, D, F>
+where
+    D: Dialect + ?Sized,
+    F: Fn(&str) -> Option<String>,
+{
+    fn write_groups(&mut self, expr_groups: &ExprGroups, span: Span) -> Result<(), Error> {
+        if expr_groups.is_empty() {
+            return Err(Error::new(ErrorKind::Empty, Token::None, span));
+        }
+
+        for (i, expr_group) in expr_groups.iter().enumerate() {
+            if i > 0 {
+                self.sql.push_str(match expr_group.join {
+                    JoinOp::And => " AND ",
+                    JoinOp::Or => " OR ",
+                });
+            }
+            self.write_item(&expr_group.item, expr_group.span, true)?;
+        }
+
+        Ok(())
+    }
+
+    // write_item writes a single group item
+    // (nested groups are wrapped in parenthesis only if `wrap` is set).
+    fn write_item(&mut self, item: &ExprGroupItem, span: Span, wrap: bool) -> Result<(), Error> {
+        match item {
+            ExprGroupItem::Expr(expr) => self.write_expr(expr),
+            ExprGroupItem::ExprGroups(expr_groups) => {
+                if wrap {
+                    self.sql.push('(');
+                }
+                self.write_groups(expr_groups, span)?;
+                if wrap {
+                    self.sql.push(')');
+                }
+                Ok(())
+            }
+            ExprGroupItem::Not(item) => {
+                self.sql.push_str("NOT (");
+                self.write_item(item, span, false)?;
+                self.sql.push(')');
+                Ok(())
+            }
+        }
+    }
+
+    fn write_expr(&mut self, expr: &Expr) -> Result<(), Error> {
+        match expr.op {
+            SignOp::None => {
+                Err(Error::new(ErrorKind::Incomplete, Token::None, expr.span)
+                    .with_expected(&["sign"]))
+            }
+            SignOp::In | SignOp::Nin => self.write_membership(expr),
+            SignOp::Like | SignOp::Nlike => {
+                let left = self.sql_operand(&expr.left, expr.span)?;
+                let right = self.like_operand(&expr.right, expr.span)?;
+                let like = self.dialect.like(&left, &right, expr.op == SignOp::Nlike);
+                self.sql.push_str(&like);
+                Ok(())
+            }
+            SignOp::AnyLike | SignOp::AnyNlike => {
+                let column = self.any_column(&expr.left, expr.span)?;
+                let right = self.like_operand(&expr.right, expr.span)?;
+                let any = self
+                    .dialect
+                    .any_like(&column, &right, expr.op == SignOp::AnyNlike);
+                self.sql.push_str(&any);
+                Ok(())
+            }
+            SignOp::AnyEq
+            | SignOp::AnyNeq
+            | SignOp::AnyLt
+            | SignOp::AnyLte
+            | SignOp::AnyGt
+            | SignOp::AnyGte => {
+                let column = self.any_column(&expr.left, expr.span)?;
+                let right = self.sql_operand(&expr.right, expr.span)?;
+                let op = match expr.op {
+                    SignOp::AnyEq => "=",
+                    SignOp::AnyNeq => "<>",
+                    SignOp::AnyLt => "<",
+                    SignOp::AnyLte => "<=",
+                    SignOp::AnyGt => ">",
+                    _ => ">=",
+                };
+                let any = self.dialect.any_compare(&column, op, &right);
+                self.sql.push_str(&any);
+                Ok(())
+            }
+            SignOp::Eq | SignOp::Neq | SignOp::Lt | SignOp::Lte | SignOp::Gt | SignOp::Gte => {
+                let left = self.operand(&expr.left, expr.span)?;
+                let right = self.operand(&expr.right, expr.span)?;
+
+                let is_eq = matches!(expr.op, SignOp::Eq | SignOp::Neq);
+                let null_check = if expr.op == SignOp::Eq {
+                    "IS NULL"
+                } else {
+                    "IS NOT NULL"
+                };
+
+                match (left, right) {
+                    (Operand::Null, Operand::Null) if is_eq => {
+                        self.sql.push_str(&format!("NULL {null_check}"));
+                    }
+                    (Operand::Sql(operand), Operand::Null)
+                    | (Operand::Null, Operand::Sql(operand))
+                        if is_eq =>
+                    {
+                        self.sql.push_str(&format!("{operand} {null_check}"));
+                    }
+                    (Operand::Sql(left), Operand::Sql(right)) => {
+                        let op = match expr.op {
+                            SignOp::Eq => "=",
+                            SignOp::Neq => "<>",
+                            SignOp::Lt => "<",
+                            SignOp::Lte => "<=",
+                            SignOp::Gt => ">",
+                            _ => ">=",
+                        };
+                        self.sql.push_str(&format!("{left} {op} {right}"));
+                    }
+                    _ => return Err(unsupported(&expr.right, expr.span)),
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    fn write_membership(&mut self, expr: &Expr) -> Result<(), Error> {
+        let left = self.sql_operand(&expr.left, expr.span)?;
+
+        let items = match &expr.right {
+            Token::List(items) => items,
+            token => return Err(unsupported(token, expr.span).with_expected(&["list"])),
+        };
+
+        let not = expr.op == SignOp::Nin;
+
+        // nothing could be a member of an empty list
+        if items.is_empty() {
+            self.sql.push_str(if not { "TRUE" } else { "FALSE" });
+            return Ok(());
+        }
+
+        // null never matches with `IN` so the null items are compiled
+        // into a separate `IS NULL` (or `IS NOT NULL`) condition
+        let mut placeholders = Vec::with_capacity(items.len());
+        let mut null = false;
+        for item in items {
+            match value(item) {
+                Some(Value::Null) => null = true,
+                Some(value) => placeholders.push(self.bind(value)),
+                None => return Err(unsupported(item, expr.span)),
+            }
+        }
+
+        let (not, join) = if not { ("NOT ", " AND ") } else { ("", " OR ") };
+
+        let mut conditions = Vec::with_capacity(2);
+        if !placeholders.is_empty() {
+            conditions.push(format!("{left} {not}IN ({})", placeholders.join(", ")));
+        }
+        if null {
+            conditions.push(format!("{left} IS {not}NULL"));
+        }
+
+        if conditions.len() > 1 {
+            self.sql.push_str(&format!("({})", conditions.join(join)));
+        } else {
+            self.sql.push_str(&conditions.join(join));
+        }
+
+        Ok(())
+    }
+
+    // operand compiles the token into a quoted column, a bound parameter placeholder or a null.
+    fn operand(&mut self, token: &Token, span: Span) -> Result<Operand, Error> {
+        if let Token::Identifier(name) = token {
+            return match (self.columns)(name) {
+                Some(column) => Ok(Operand::Sql(self.dialect.quote_identifier(&column))),
+                None => Err(Error::new(
+                    ErrorKind::UnknownIdentifier,
+                    token.clone(),
+                    span,
+                )),
+            };
+        }
+
+        match value(token) {
+            Some(Value::Null) => Ok(Operand::Null),
+            Some(value) => Ok(Operand::Sql(self.bind(value))),
+            None => Err(unsupported(token, span)),
+        }
+    }
+
+    // sql_operand is similar to `operand` but doesn't allow null operands.
+    fn sql_operand(&mut self, token: &Token, span: Span) -> Result<String, Error> {
+        match self.operand(token, span)? {
+            Operand::Sql(operand) => Ok(operand),
+            Operand::Null => Err(unsupported(token, span)),
+        }
+    }
+
+    // like_operand compiles the right operand of a like expression,
+    // wrapping it with `%` (literal values are also escaped).
+    fn like_operand(&mut self, token: &Token, span: Span) -> Result<String, Error> {
+        let pattern = match token {
+            Token::Text(value) | Token::Number(value) | Token::Bool(value) => {
+                format!("%{}%", escape_like(value))
+            }
+            Token::Identifier(_) => {
+                let column = self.sql_operand(token, span)?;
+                return Ok(self.dialect.concat(&["'%'", &column, "'%'"]));
+            }
+            _ => return Err(unsupported(token, span)),
+        };
+
+        Ok(self.bind(Value::Text(pattern)))
+    }
+
+    // any_column compiles the left operand of the `Any*` operators
+    // (it must be an array column).
+    fn any_column(&mut self, token: &Token, span: Span) -> Result<String, Error> {
+        if !matches!(token, Token::Identifier(_)) {
+            return Err(unsupported(token, span).with_expected(&["identifier"]));
+        }
+
+        self.sql_operand(token, span)
+    }
+
+    // bind registers a new parameter value and returns its placeholder.
+    fn bind(&mut self, value: Value) -> String {
+        self.params.push(value);
+        self.dialect.placeholder(self.params.len())
+    }
+}
+
+// value converts a literal token into a parameter value.
+fn value(token: &Token) -> Option<Value> {
+    match token {
+        Token::Null(_) => Some(Value::Null),
+        Token::Bool(value) => Some(Value::Bool(value == "true")),
+        Token::Text(value) => Some(Value::Text(value.to_string())),
+        Token::Number(value) => match value.parse::<i64>() {
+            Ok(number) => Some(Value::Int(number)),
+            Err(_) => value.parse::<f64>().ok().map(Value::Float),
+        },
+        _ => None,
+    }
+}
+
+fn unsupported(token: &Token, span: Span) -> Error {
+    Error::new(ErrorKind::UnsupportedOperand, token.clone(), span)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::ErrorKind,
+        parser::parse,
+        sql::{compile, quote_identifier, Value},
+    };
+
+    fn columns(name: &str) -> Option<String> {
+        match name {
+            "id" | "a" | "b" | "c" | "tags" | "author.name" => Some(name.to_string()),
+            "@request.auth.id" => Some("auth_id".to_string()),
+            "q\"uoted" => Some(name.to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_compile() {
+        struct Scenario {
+            input: &'static str,
+            expected_sql: &'static str,
+            expected_params: Vec<Value>,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"id > 1",
+                expected_sql: r#""id" > $1"#,
+                expected_params: vec![Value::Int(1)],
+            },
+            Scenario {
+                input: r"a = 'x' && b != -1.5 || c <= true && id >= a && b < 'y'",
+                expected_sql: r#""a" = $1 AND "b" <> $2 OR "c" <= $3 AND "id" >= "a" AND "b" < $4"#,
+                expected_params: vec![
+                    Value::Text("x".into()),
+                    Value::Float(-1.5),
+                    Value::Bool(true),
+                    Value::Text("y".into()),
+                ],
+            },
+            Scenario {
+                input: r"a = null || null != b || null = null",
+                expected_sql: r#""a" IS NULL OR "b" IS NOT NULL OR NULL IS NULL"#,
+                expected_params: vec![],
+            },
+            Scenario {
+                input: r"author.name ~ '50%_o\ff' && a !~ 1 && b ~ c",
+                expected_sql: r#""author"."name" ILIKE $1 ESCAPE '\' AND "a" NOT ILIKE $2 ESCAPE '\' AND "b" ILIKE ('%' || "c" || '%') ESCAPE '\'"#,
+                expected_params: vec![
+                    Value::Text(r"%50\%\_o\\ff%".into()),
+                    Value::Text("%1%".into()),
+                ],
+            },
+            Scenario {
+                input: r"a in ['x', 2, null] && b !in [true] && c in [] && id !in []",
+                expected_sql: r#"("a" IN ($1, $2) OR "a" IS NULL) AND "b" NOT IN ($3) AND FALSE AND TRUE"#,
+                expected_params: vec![Value::Text("x".into()), Value::Int(2), Value::Bool(true)],
+            },
+            Scenario {
+                input: r"a !in [1, null] || b in [null, null] || c !in [null]",
+                expected_sql: r#"("a" NOT IN ($1) AND "a" IS NOT NULL) OR "b" IS NULL OR "c" IS NOT NULL"#,
+                expected_params: vec![Value::Int(1)],
+            },
+            Scenario {
+                input: r"tags ?= 'x' && tags ?!= 'y' && tags ?< 1 && tags ?<= 2 && tags ?> 3 && tags ?>= a",
+                expected_sql: r#"$1 = ANY("tags") AND $2 <> ANY("tags") AND $3 > ANY("tags") AND $4 >= ANY("tags") AND $5 < ANY("tags") AND "a" <= ANY("tags")"#,
+                expected_params: vec![
+                    Value::Text("x".into()),
+                    Value::Text("y".into()),
+                    Value::Int(1),
+                    Value::Int(2),
+                    Value::Int(3),
+                ],
+            },
+            Scenario {
+                input: r"tags ?~ 'x' || tags ?!~ 'y'",
+                expected_sql: r#"EXISTS (SELECT 1 FROM unnest("tags") AS _v WHERE _v ILIKE $1 ESCAPE '\') OR EXISTS (SELECT 1 FROM unnest("tags") AS _v WHERE _v NOT ILIKE $2 ESCAPE '\')"#,
+                expected_params: vec![Value::Text("%x%".into()), Value::Text("%y%".into())],
+            },
+            Scenario {
+                input: r"a = 1 && (b = 2 || !(c = 3 && !id = 4)) || !(!(a = 5))",
+                expected_sql: r#""a" = $1 AND ("b" = $2 OR NOT ("c" = $3 AND NOT ("id" = $4))) OR NOT (NOT ("a" = $5))"#,
+                expected_params: vec![
+                    Value::Int(1),
+                    Value::Int(2),
+                    Value::Int(3),
+                    Value::Int(4),
+                    Value::Int(5),
+                ],
+            },
+            Scenario {
+                input: r"@request.auth.id = id && 'x' = 'y'",
+                expected_sql: r#""auth_id" = "id" AND $1 = $2"#,
+                expected_params: vec![Value::Text("x".into()), Value::Text("y".into())],
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let fragment = compile(&parse(scenario.input).unwrap(), columns).unwrap();
+
+            assert!(
+                fragment.sql == scenario.expected_sql,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected_sql,
+                fragment.sql
+            );
+            assert!(
+                fragment.params == scenario.expected_params,
+                "({}) Expected {:?}, got {:?}",
+                i,
+                scenario.expected_params,
+                fragment.params
+            );
+        }
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        struct Scenario {
+            input: &'static str,
+            kind: ErrorKind,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"unknown = 1",
+                kind: ErrorKind::UnknownIdentifier,
+            },
+            Scenario {
+                input: r"a = 1 || (b = unknown)",
+                kind: ErrorKind::UnknownIdentifier,
+            },
+            Scenario {
+                input: r"a > null",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"a ~ null",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"'x' ?= 1",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+            Scenario {
+                input: r"null in [1]",
+                kind: ErrorKind::UnsupportedOperand,
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            match compile(&parse(scenario.input).unwrap(), columns) {
+                Ok(fragment) => panic!("({}) Expected error, got {}", i, fragment.sql),
+                Err(err) => assert!(
+                    err.kind == scenario.kind,
+                    "({}) Expected {:?}, got {:?}",
+                    i,
+                    scenario.kind,
+                    err.kind
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        let scenarios = [
+            ("a", r#""a""#),
+            ("a.b.c", r#""a"."b"."c""#),
+            ("q\"uoted", r#""q""uoted""#),
+        ];
+
+        for (i, (name, expected)) in scenarios.iter().enumerate() {
+            let result = quote_identifier(name);
+            assert!(
+                result == *expected,
+                "({}) Expected {}, got {}",
+                i,
+                expected,
+                result
+            );
+        }
+    }
+}
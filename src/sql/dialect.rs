@@ -0,0 +1,281 @@
+// Dialect represents the database specific parts of the generated SQL.
+//
+// All provided method arguments are already compiled SQL expressions
+// (quoted columns, placeholders, etc.). Dialects with positional (aka. `?`)
+// placeholders must keep the arguments in the provided order.
+pub trait Dialect {
+    // placeholder returns the placeholder of the N-th (1-based) bound parameter.
+    fn placeholder(&self, index: usize) -> String;
+
+    // quote_identifier quotes the provided (possibly dot separated) column name.
+    fn quote_identifier(&self, name: &str) -> String;
+
+    // like returns a case-insensitive like expression where the `pattern`
+    // uses `\` as escape character.
+    fn like(&self, left: &str, pattern: &str, negated: bool) -> String {
+        let not = if negated { "NOT " } else { "" };
+        format!("{left} {not}LIKE {pattern} ESCAPE '\\'")
+    }
+
+    // concat returns an expression concatenating the provided text values.
+    fn concat(&self, values: &[&str]) -> String {
+        format!("({})", values.join(" || "))
+    }
+
+    // array_elements returns the `FROM` clause source listing the elements
+    // of the array column and the expression of a single element.
+    fn array_elements(&self, column: &str) -> (String, String);
+
+    // any_compare returns an expression checking if any element of the array
+    // column satisfies `element <op> right` (op is one of `=`, `<>`, `<`, `<=`, `>`, `>=`).
+    fn any_compare(&self, column: &str, op: &str, right: &str) -> String {
+        let (source, element) = self.array_elements(column);
+        format!("EXISTS (SELECT 1 FROM {source} WHERE {element} {op} {right})")
+    }
+
+    // any_like returns an expression checking if any element of the array
+    // column matches the like `pattern`.
+    fn any_like(&self, column: &str, pattern: &str, negated: bool) -> String {
+        let (source, element) = self.array_elements(column);
+        format!(
+            "EXISTS (SELECT 1 FROM {source} WHERE {})",
+            self.like(&element, pattern, negated)
+        )
+    }
+}
+
+// Postgres is the PostgreSQL dialect (`$N` placeholders and native arrays).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Postgres;
+
+impl Dialect for Postgres {
+    fn placeholder(&self, index: usize) -> String {
+        format!("${index}")
+    }
+
+    fn quote_identifier(&self, name: &str) -> String {
+        quote_with(name, '"')
+    }
+
+    fn like(&self, left: &str, pattern: &str, negated: bool) -> String {
+        let not = if negated { "NOT " } else { "" };
+        format!("{left} {not}ILIKE {pattern} ESCAPE '\\'")
+    }
+
+    fn array_elements(&self, column: &str) -> (String, String) {
+        (format!("unnest({column}) AS _v"), "_v".to_string())
+    }
+
+    fn any_compare(&self, column: &str, op: &str, right: &str) -> String {
+        // the array elements are on the right side of the comparison
+        let op = match op {
+            "<" => ">",
+            "<=" => ">=",
+            ">" => "<",
+            ">=" => "<=",
+            op => op,
+        };
+        format!("{right} {op} ANY({column})")
+    }
+}
+
+// Sqlite is the SQLite dialect (`?` placeholders and JSON array columns).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sqlite;
+
+impl Dialect for Sqlite {
+    fn placeholder(&self, _index: usize) -> String {
+        "?".to_string()
+    }
+
+    fn quote_identifier(&self, name: &str) -> String {
+        quote_with(name, '"')
+    }
+
+    fn array_elements(&self, column: &str) -> (String, String) {
+        (
+            format!("json_each({column})"),
+            "json_each.value".to_string(),
+        )
+    }
+}
+
+// MySql is the MySQL dialect (`?` placeholders, backtick quoted
+// identifiers and JSON array columns).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MySql;
+
+impl Dialect for MySql {
+    fn placeholder(&self, _index: usize) -> String {
+        "?".to_string()
+    }
+
+    fn quote_identifier(&self, name: &str) -> String {
+        quote_with(name, '`')
+    }
+
+    fn like(&self, left: &str, pattern: &str, negated: bool) -> String {
+        let not = if negated { "NOT " } else { "" };
+        format!("{left} {not}LIKE {pattern} ESCAPE '\\\\'")
+    }
+
+    fn concat(&self, values: &[&str]) -> String {
+        format!("CONCAT({})", values.join(", "))
+    }
+
+    fn array_elements(&self, column: &str) -> (String, String) {
+        (
+            format!("JSON_TABLE({column}, '$[*]' COLUMNS (_v JSON PATH '$')) AS _t"),
+            "_t._v".to_string(),
+        )
+    }
+
+    fn any_compare(&self, column: &str, op: &str, right: &str) -> String {
+        if op == "=" {
+            return format!("JSON_CONTAINS({column}, JSON_ARRAY({right}))");
+        }
+
+        let (source, element) = self.array_elements(column);
+        format!("EXISTS (SELECT 1 FROM {source} WHERE {element} {op} {right})")
+    }
+}
+
+// quote_with wraps each dot separated part of the name
+// with the quote char (doubling the inner quote chars).
+fn quote_with(name: &str, quote: char) -> String {
+    let escaped = quote.to_string().repeat(2);
+
+    name.split('.')
+        .map(|part| format!("{quote}{}{quote}", part.replace(quote, &escaped)))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::parse,
+        sql::{compile_with, Dialect, MySql, Postgres, Sqlite, Value},
+    };
+
+    // Oracle is a minimal custom dialect relying on the default implementations.
+    struct Oracle;
+
+    impl Dialect for Oracle {
+        fn placeholder(&self, index: usize) -> String {
+            format!(":{index}")
+        }
+
+        fn quote_identifier(&self, name: &str) -> String {
+            name.to_uppercase()
+        }
+
+        fn array_elements(&self, column: &str) -> (String, String) {
+            (format!("TABLE({column})"), "COLUMN_VALUE".to_string())
+        }
+    }
+
+    #[test]
+    fn test_compile_with() {
+        struct Scenario {
+            dialect: &'static dyn Dialect,
+            input: &'static str,
+            expected_sql: &'static str,
+            expected_params: Vec<Value>,
+        }
+
+        let input = r"a.b = 1 && (c ~ 'x%' || c !~ d) && tags ?= 'y' && tags ?> 2 && tags ?!~ 'z' && a.b in ['w']";
+
+        let scenarios = [
+            Scenario {
+                dialect: &Postgres,
+                input,
+                expected_sql: r#""a"."b" = $1 AND ("c" ILIKE $2 ESCAPE '\' OR "c" NOT ILIKE ('%' || "d" || '%') ESCAPE '\') AND $3 = ANY("tags") AND $4 < ANY("tags") AND EXISTS (SELECT 1 FROM unnest("tags") AS _v WHERE _v NOT ILIKE $5 ESCAPE '\') AND "a"."b" IN ($6)"#,
+                expected_params: vec![
+                    Value::Int(1),
+                    Value::Text(r"%x\%%".into()),
+                    Value::Text("y".into()),
+                    Value::Int(2),
+                    Value::Text("%z%".into()),
+                    Value::Text("w".into()),
+                ],
+            },
+            Scenario {
+                dialect: &Sqlite,
+                input,
+                expected_sql: r#""a"."b" = ? AND ("c" LIKE ? ESCAPE '\' OR "c" NOT LIKE ('%' || "d" || '%') ESCAPE '\') AND EXISTS (SELECT 1 FROM json_each("tags") WHERE json_each.value = ?) AND EXISTS (SELECT 1 FROM json_each("tags") WHERE json_each.value > ?) AND EXISTS (SELECT 1 FROM json_each("tags") WHERE json_each.value NOT LIKE ? ESCAPE '\') AND "a"."b" IN (?)"#,
+                expected_params: vec![
+                    Value::Int(1),
+                    Value::Text(r"%x\%%".into()),
+                    Value::Text("y".into()),
+                    Value::Int(2),
+                    Value::Text("%z%".into()),
+                    Value::Text("w".into()),
+                ],
+            },
+            Scenario {
+                dialect: &MySql,
+                input,
+                expected_sql: r"`a`.`b` = ? AND (`c` LIKE ? ESCAPE '\\' OR `c` NOT LIKE CONCAT('%', `d`, '%') ESCAPE '\\') AND JSON_CONTAINS(`tags`, JSON_ARRAY(?)) AND EXISTS (SELECT 1 FROM JSON_TABLE(`tags`, '$[*]' COLUMNS (_v JSON PATH '$')) AS _t WHERE _t._v > ?) AND EXISTS (SELECT 1 FROM JSON_TABLE(`tags`, '$[*]' COLUMNS (_v JSON PATH '$')) AS _t WHERE _t._v NOT LIKE ? ESCAPE '\\') AND `a`.`b` IN (?)",
+                expected_params: vec![
+                    Value::Int(1),
+                    Value::Text(r"%x\%%".into()),
+                    Value::Text("y".into()),
+                    Value::Int(2),
+                    Value::Text("%z%".into()),
+                    Value::Text("w".into()),
+                ],
+            },
+            Scenario {
+                dialect: &Oracle,
+                input: r"a = 'x' && tags ?~ 'y' && tags ?<= b",
+                expected_sql: r"A = :1 AND EXISTS (SELECT 1 FROM TABLE(TAGS) WHERE COLUMN_VALUE LIKE :2 ESCAPE '\') AND EXISTS (SELECT 1 FROM TABLE(TAGS) WHERE COLUMN_VALUE <= B)",
+                expected_params: vec![Value::Text("x".into()), Value::Text("%y%".into())],
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let fragment =
+                compile_with(&parse(scenario.input).unwrap(), scenario.dialect, |name| {
+                    Some(name.to_string())
+                })
+                .unwrap();
+
+            assert!(
+                fragment.sql == scenario.expected_sql,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected_sql,
+                fragment.sql
+            );
+            assert!(
+                fragment.params == scenario.expected_params,
+                "({}) Expected {:?}, got {:?}",
+                i,
+                scenario.expected_params,
+                fragment.params
+            );
+        }
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        let scenarios: [(&dyn Dialect, &str, &str); 4] = [
+            (&Postgres, r#"a."b""#, r#""a"."""b""""#),
+            (&Sqlite, "a.b", r#""a"."b""#),
+            (&MySql, "a.b", "`a`.`b`"),
+            (&MySql, "a`b", "`a``b`"),
+        ];
+
+        for (i, (dialect, name, expected)) in scenarios.iter().enumerate() {
+            let result = dialect.quote_identifier(name);
+            assert!(
+                result == *expected,
+                "({}) Expected {}, got {}",
+                i,
+                expected,
+                result
+            );
+        }
+    }
+}
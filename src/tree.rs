@@ -0,0 +1,161 @@
+use crate::{
+    parser::{Expr, ExprGroupItem, ExprGroups},
+    scanner::JoinOp,
+};
+
+// ExprTree represents a binary expression tree where the join operators
+// precedence is already resolved (aka. `&&` binds tighter than `||`).
+//
+// For example `a=1 || b=2 && c=3` is represented as `a=1 || (b=2 && c=3)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExprTree<'a> {
+    And(Box<ExprTree<'a>>, Box<ExprTree<'a>>),
+    Or(Box<ExprTree<'a>>, Box<ExprTree<'a>>),
+    Not(Box<ExprTree<'a>>),
+    Comparison(Expr<'a>),
+}
+
+impl<'a> ExprTree<'a> {
+    // and creates a new `And` node from the provided operands.
+    pub fn and(left: ExprTree<'a>, right: ExprTree<'a>) -> Self {
+        Self::And(Box::new(left), Box::new(right))
+    }
+
+    // or creates a new `Or` node from the provided operands.
+    pub fn or(left: ExprTree<'a>, right: ExprTree<'a>) -> Self {
+        Self::Or(Box::new(left), Box::new(right))
+    }
+
+    // from_groups converts the flat expression groups into an expression tree.
+    //
+    // Consecutive `&&` joined items are combined first and the resulting
+    // conjunctions are then combined with `||` (both left associative).
+    //
+    // Returns `None` if there are no expressions to convert.
+    pub fn from_groups(groups: &ExprGroups<'a>) -> Option<Self> {
+        let mut disjunction: Option<ExprTree<'a>> = None;
+        let mut conjunction: Option<ExprTree<'a>> = None;
+
+        for group in groups.get() {
+            let item = match Self::from_item(&group.item) {
+                Some(item) => item,
+                None => continue,
+            };
+
+            conjunction = match (group.join, conjunction.take()) {
+                (JoinOp::Or, Some(prev)) => {
+                    disjunction = Some(match disjunction.take() {
+                        Some(left) => Self::or(left, prev),
+                        None => prev,
+                    });
+                    Some(item)
+                }
+                (JoinOp::And, Some(prev)) => Some(Self::and(prev, item)),
+                (_, None) => Some(item),
+            };
+        }
+
+        match (disjunction, conjunction) {
+            (Some(left), Some(right)) => Some(Self::or(left, right)),
+            (left, right) => left.or(right),
+        }
+    }
+
+    fn from_item(item: &ExprGroupItem<'a>) -> Option<Self> {
+        match item {
+            ExprGroupItem::Expr(expr) => Some(Self::Comparison(expr.clone())),
+            ExprGroupItem::ExprGroups(groups) => Self::from_groups(groups),
+            ExprGroupItem::Not(item) => Self::from_item(item).map(|operand| !operand),
+        }
+    }
+}
+
+// Negating a tree wraps it in a new `Not` node.
+impl<'a> std::ops::Not for ExprTree<'a> {
+    type Output = ExprTree<'a>;
+
+    fn not(self) -> Self::Output {
+        ExprTree::Not(Box::new(self))
+    }
+}
+
+impl std::fmt::Display for ExprTree<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExprTree::And(left, right) => write!(f, "{{{} {} {}}}", JoinOp::And, left, right),
+            ExprTree::Or(left, right) => write!(f, "{{{} {} {}}}", JoinOp::Or, left, right),
+            ExprTree::Not(operand) => write!(f, "!{operand}"),
+            ExprTree::Comparison(expr) => write!(f, "{expr}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parse, tree::ExprTree};
+
+    #[test]
+    fn test_expr_tree_from_groups() {
+        struct Scenario {
+            input: &'static str,
+            expected_print: &'static str,
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"a=1",
+                expected_print: r"{{identifier a} = {number 1}}",
+            },
+            Scenario {
+                input: r"(a=1)",
+                expected_print: r"{{identifier a} = {number 1}}",
+            },
+            Scenario {
+                input: r"a=1 && b=2 && c=3",
+                expected_print: r"{&& {&& {{identifier a} = {number 1}} {{identifier b} = {number 2}}} {{identifier c} = {number 3}}}",
+            },
+            Scenario {
+                input: r"a=1 || b=2 || c=3",
+                expected_print: r"{|| {|| {{identifier a} = {number 1}} {{identifier b} = {number 2}}} {{identifier c} = {number 3}}}",
+            },
+            Scenario {
+                input: r"a=1 || b=2 && c=3",
+                expected_print: r"{|| {{identifier a} = {number 1}} {&& {{identifier b} = {number 2}} {{identifier c} = {number 3}}}}",
+            },
+            Scenario {
+                input: r"a=1 && b=2 || c=3",
+                expected_print: r"{|| {&& {{identifier a} = {number 1}} {{identifier b} = {number 2}}} {{identifier c} = {number 3}}}",
+            },
+            Scenario {
+                input: r"a=1 && b=2 || c=3 && d=4",
+                expected_print: r"{|| {&& {{identifier a} = {number 1}} {{identifier b} = {number 2}}} {&& {{identifier c} = {number 3}} {{identifier d} = {number 4}}}}",
+            },
+            Scenario {
+                input: r"(a=1 || b=2) && c=3",
+                expected_print: r"{&& {|| {{identifier a} = {number 1}} {{identifier b} = {number 2}}} {{identifier c} = {number 3}}}",
+            },
+            Scenario {
+                input: r"a=1 && (b=2 || c=3 && d=4)",
+                expected_print: r"{&& {{identifier a} = {number 1}} {|| {{identifier b} = {number 2}} {&& {{identifier c} = {number 3}} {{identifier d} = {number 4}}}}}",
+            },
+            Scenario {
+                input: r"!a=1 || !(b=2 && !(!c=3))",
+                expected_print: r"{|| !{{identifier a} = {number 1}} !{&& {{identifier b} = {number 2}} !!{{identifier c} = {number 3}}}}",
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let groups = parse(scenario.input).unwrap();
+            let tree = ExprTree::from_groups(&groups).unwrap();
+            let tree_print = tree.to_string();
+
+            assert!(
+                tree_print == scenario.expected_print,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected_print,
+                tree_print
+            );
+        }
+    }
+}
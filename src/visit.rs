@@ -0,0 +1,251 @@
+use crate::{
+    parser::{Expr, ExprGroup, ExprGroupItem, ExprGroups},
+    scanner::Token,
+};
+
+// Visitor represents a read-only AST traversal.
+//
+// Each method by default walks the children of the visited node
+// (see the `walk_*` functions), so an implementation needs to override
+// only the hooks it is interested in. Call the corresponding `walk_*`
+// function from an overridden method to continue the traversal.
+pub trait Visitor {
+    fn visit_groups(&mut self, expr_groups: &ExprGroups<'_>) {
+        walk_groups(self, expr_groups)
+    }
+
+    fn visit_group(&mut self, expr_group: &ExprGroup<'_>) {
+        walk_group(self, expr_group)
+    }
+
+    fn visit_item(&mut self, item: &ExprGroupItem<'_>) {
+        walk_item(self, item)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr<'_>) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_token(&mut self, token: &Token<'_>) {
+        walk_token(self, token)
+    }
+}
+
+// walk_groups visits each group.
+pub fn walk_groups<V: Visitor + ?Sized>(visitor: &mut V, expr_groups: &ExprGroups<'_>) {
+    for expr_group in expr_groups {
+        visitor.visit_group(expr_group);
+    }
+}
+
+// walk_group visits the group item.
+pub fn walk_group<V: Visitor + ?Sized>(visitor: &mut V, expr_group: &ExprGroup<'_>) {
+    visitor.visit_item(&expr_group.item);
+}
+
+// walk_item visits the expression, the nested groups or the negated item.
+pub fn walk_item<V: Visitor + ?Sized>(visitor: &mut V, item: &ExprGroupItem<'_>) {
+    match item {
+        ExprGroupItem::Expr(expr) => visitor.visit_expr(expr),
+        ExprGroupItem::ExprGroups(expr_groups) => visitor.visit_groups(expr_groups),
+        ExprGroupItem::Not(item) => visitor.visit_item(item),
+    }
+}
+
+// walk_expr visits the left and right operand tokens.
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr<'_>) {
+    visitor.visit_token(&expr.left);
+    visitor.visit_token(&expr.right);
+}
+
+// walk_token visits the list items (other tokens don't have children).
+pub fn walk_token<V: Visitor + ?Sized>(visitor: &mut V, token: &Token<'_>) {
+    for item in token.items() {
+        visitor.visit_token(item);
+    }
+}
+
+// VisitorMut represents an AST traversal that could rewrite the visited nodes in place.
+//
+// Similar to `Visitor`, each method by default walks the children of the
+// visited node (see the `walk_*_mut` functions). A node could be also replaced
+// as a whole (eg. `*item = ExprGroupItem::Not(...)`) without walking it.
+pub trait VisitorMut {
+    fn visit_groups_mut(&mut self, expr_groups: &mut ExprGroups<'_>) {
+        walk_groups_mut(self, expr_groups)
+    }
+
+    fn visit_group_mut(&mut self, expr_group: &mut ExprGroup<'_>) {
+        walk_group_mut(self, expr_group)
+    }
+
+    fn visit_item_mut(&mut self, item: &mut ExprGroupItem<'_>) {
+        walk_item_mut(self, item)
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr<'_>) {
+        walk_expr_mut(self, expr)
+    }
+
+    fn visit_token_mut(&mut self, token: &mut Token<'_>) {
+        walk_token_mut(self, token)
+    }
+}
+
+// walk_groups_mut visits each group.
+pub fn walk_groups_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr_groups: &mut ExprGroups<'_>) {
+    for expr_group in expr_groups {
+        visitor.visit_group_mut(expr_group);
+    }
+}
+
+// walk_group_mut visits the group item.
+pub fn walk_group_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr_group: &mut ExprGroup<'_>) {
+    visitor.visit_item_mut(&mut expr_group.item);
+}
+
+// walk_item_mut visits the expression, the nested groups or the negated item.
+pub fn walk_item_mut<V: VisitorMut + ?Sized>(visitor: &mut V, item: &mut ExprGroupItem<'_>) {
+    match item {
+        ExprGroupItem::Expr(expr) => visitor.visit_expr_mut(expr),
+        ExprGroupItem::ExprGroups(expr_groups) => visitor.visit_groups_mut(expr_groups),
+        ExprGroupItem::Not(item) => visitor.visit_item_mut(item),
+    }
+}
+
+// walk_expr_mut visits the left and right operand tokens.
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr<'_>) {
+    visitor.visit_token_mut(&mut expr.left);
+    visitor.visit_token_mut(&mut expr.right);
+}
+
+// walk_token_mut visits the list items (other tokens don't have children).
+pub fn walk_token_mut<V: VisitorMut + ?Sized>(visitor: &mut V, token: &mut Token<'_>) {
+    if let Token::List(items) = token {
+        for item in items {
+            visitor.visit_token_mut(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parser::{parse, Expr, ExprGroupItem, ExprGroups},
+        scanner::{SignOp, Token},
+        visit::{
+            walk_expr, walk_groups, walk_item_mut, walk_token, walk_token_mut, Visitor, VisitorMut,
+        },
+    };
+
+    #[test]
+    fn test_visitor() {
+        // Stats collects the identifiers and counts the literals, expressions and groups.
+        #[derive(Default)]
+        struct Stats {
+            identifiers: Vec<String>,
+            literals: usize,
+            exprs: usize,
+            groups: usize,
+        }
+
+        impl Visitor for Stats {
+            fn visit_groups(&mut self, expr_groups: &ExprGroups<'_>) {
+                self.groups += 1;
+                walk_groups(self, expr_groups);
+            }
+
+            fn visit_expr(&mut self, expr: &Expr<'_>) {
+                self.exprs += 1;
+                walk_expr(self, expr);
+            }
+
+            fn visit_token(&mut self, token: &Token<'_>) {
+                match token {
+                    Token::Identifier(name) => self.identifiers.push(name.to_string()),
+                    Token::List(_) => walk_token(self, token),
+                    _ => self.literals += 1,
+                }
+            }
+        }
+
+        let mut stats = Stats::default();
+        stats.visit_groups(&parse("a = 1 && (b ~ c || !(d in ['x', 2]))").unwrap());
+
+        assert!(
+            stats.identifiers == ["a", "b", "c", "d"],
+            "Expected identifiers [a b c d], got {:?}",
+            stats.identifiers
+        );
+        assert!(
+            stats.literals == 3,
+            "Expected 3 literals, got {}",
+            stats.literals
+        );
+        assert!(
+            stats.exprs == 3,
+            "Expected 3 expressions, got {}",
+            stats.exprs
+        );
+        assert!(stats.groups == 3, "Expected 3 groups, got {}", stats.groups);
+    }
+
+    #[test]
+    fn test_visitor_mut() {
+        struct Scenario {
+            input: &'static str,
+            expected: &'static str,
+        }
+
+        // Rewriter renames the identifiers, lowercases the text literals
+        // and replaces the `!=` expressions with negated `=` ones.
+        struct Rewriter;
+
+        impl VisitorMut for Rewriter {
+            fn visit_item_mut(&mut self, item: &mut ExprGroupItem<'_>) {
+                if let ExprGroupItem::Expr(expr) = item {
+                    if expr.op == SignOp::Neq {
+                        expr.op = SignOp::Eq;
+                        *item = ExprGroupItem::Not(Box::new(item.clone()));
+                    }
+                }
+
+                walk_item_mut(self, item);
+            }
+
+            fn visit_token_mut(&mut self, token: &mut Token<'_>) {
+                match token {
+                    Token::Identifier(name) if name == "user" => {
+                        *token = Token::Identifier("author.name".into())
+                    }
+                    Token::Text(value) => *token = Token::Text(value.to_lowercase().into()),
+                    _ => walk_token_mut(self, token),
+                }
+            }
+        }
+
+        let scenarios = [
+            Scenario {
+                input: r"user = 'JOHN'",
+                expected: r"[{&& {{identifier author.name} = {text john}}}]",
+            },
+            Scenario {
+                input: r"a != 1 || (user in ['A', 1] && !(b != 'X'))",
+                expected: r"[{&& !{{identifier a} = {number 1}}} {|| [{&& {{identifier author.name} in {list [{text a} {number 1}]}}} {&& ![{&& !{{identifier b} = {text x}}}]}]}]",
+            },
+        ];
+
+        for (i, scenario) in scenarios.iter().enumerate() {
+            let mut result = parse(scenario.input).unwrap();
+            Rewriter.visit_groups_mut(&mut result);
+
+            assert!(
+                result.to_string() == scenario.expected,
+                "({}) Expected {}, got {}",
+                i,
+                scenario.expected,
+                result
+            );
+        }
+    }
+}
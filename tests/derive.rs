@@ -0,0 +1,171 @@
+// Checks the `#[derive(Filterable)]` expansion from outside of the crate.
+
+use fexpr::filter::{compile, compile_for, Filterable};
+use fexpr::{parse, ErrorKind};
+
+// silence the unused_crate_dependencies lint for the other dev and optional dependencies
+use fexpr_derive as _;
+use once_cell as _;
+use proptest as _;
+use regex as _;
+#[cfg(feature = "serde")]
+use serde as _;
+use serde_json as _;
+use stats_alloc as _;
+
+#[derive(Filterable)]
+struct Category {
+    name: String,
+    parent: Option<Box<Category>>,
+    children: Vec<Category>,
+}
+
+#[derive(Filterable)]
+struct Author {
+    name: String,
+    #[filter(rename = "mail")]
+    email: Option<String>,
+}
+
+#[derive(Filterable)]
+struct Post {
+    id: u64,
+    r#type: String,
+    author: Author,
+    editor: Option<Author>,
+    category: Category,
+    tags: Vec<String>,
+    #[filter(skip)]
+    #[allow(dead_code)]
+    secret: String,
+}
+
+#[derive(Filterable)]
+struct Page<T> {
+    total: usize,
+    items: Vec<T>,
+}
+
+fn category(name: &str, parent: Option<Category>) -> Category {
+    Category {
+        name: name.to_string(),
+        parent: parent.map(Box::new),
+        children: vec![],
+    }
+}
+
+fn post(id: u64, category: Category) -> Post {
+    Post {
+        id,
+        r#type: "article".to_string(),
+        author: Author {
+            name: "Jane".to_string(),
+            email: None,
+        },
+        editor: None,
+        category,
+        tags: vec!["rust".to_string(), "Parser".to_string()],
+        secret: "xyz".to_string(),
+    }
+}
+
+#[test]
+fn test_derive_filterable_matches() {
+    let mut root = category("root", None);
+    root.children = vec![category("a", None), category("b", None)];
+
+    let page = Page {
+        total: 2,
+        items: vec![
+            post(1, category("rust", Some(category("lang", Some(root))))),
+            post(2, category("misc", None)),
+        ],
+    };
+
+    struct Scenario {
+        input: &'static str,
+        expected: bool,
+    }
+
+    let scenarios = [
+        // nested and renamed fields
+        Scenario {
+            input: r"id = 1 && type = 'article' && author.name = 'Jane' && author.mail = null",
+            expected: true,
+        },
+        // recursive fields
+        Scenario {
+            input: r"category.name = 'rust' && category.parent.name = 'lang' && category.parent.parent.name = 'root'",
+            expected: true,
+        },
+        Scenario {
+            input: r"category.parent.parent.parent.name = null && category.parent.parent.children.name ?= 'b'",
+            expected: true,
+        },
+        // optional fields
+        Scenario {
+            input: r"editor = null && editor.name = null",
+            expected: true,
+        },
+        Scenario {
+            input: r"editor != null || author = null || category = null",
+            expected: false,
+        },
+        // vec fields
+        Scenario {
+            input: r"tags ?~ 'PARS' && tags ?!= 'go'",
+            expected: true,
+        },
+        // skipped fields
+        Scenario {
+            input: r"secret = 'xyz'",
+            expected: false,
+        },
+    ];
+
+    for scenario in scenarios {
+        let filter = compile_for::<Post>(&parse(scenario.input).unwrap()).unwrap();
+        let result = filter.matches(&page.items[0]);
+
+        assert!(
+            result == scenario.expected,
+            "({}) Expected {}, got {}",
+            scenario.input,
+            scenario.expected,
+            result
+        );
+    }
+
+    // generic structs
+    let filter = compile(&parse(r"total = 2 && items.category.name ?= 'misc'").unwrap()).unwrap();
+    assert!(filter.matches(&page), "Expected the page to match");
+}
+
+#[test]
+fn test_derive_filterable_types() {
+    let scenarios = [
+        (r"items.category.parent.parent.name ?= 1", true),
+        (r"items.id ?= 'x'", true),
+        (
+            r"items.category.parent.name ?= 'x' && items.author.mail ?= null",
+            false,
+        ),
+        (r"total > 1.5 && secret = 1", false),
+    ];
+
+    for (input, mismatch) in scenarios {
+        let result = compile_for::<Page<Post>>(&parse(input).unwrap());
+        let result = match result {
+            Err(err) => err.kind == ErrorKind::TypeMismatch,
+            Ok(_) => false,
+        };
+
+        assert!(
+            result == mismatch,
+            "({}) Expected type mismatch {}, got {}",
+            input,
+            mismatch,
+            result
+        );
+    }
+}
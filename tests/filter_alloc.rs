@@ -0,0 +1,95 @@
+// Checks that matching a compiled filter doesn't allocate.
+//
+// The test is in its own integration test binary (and it is the only test in it),
+// so that no other test allocates concurrently with the counted region.
+
+use std::alloc::System;
+use std::borrow::Cow;
+
+use fexpr::filter::{self, Field, Record, Value};
+use stats_alloc::{Region, StatsAlloc, INSTRUMENTED_SYSTEM};
+
+// silence the unused_crate_dependencies lint for the other dev and optional dependencies
+#[cfg(feature = "derive")]
+use fexpr_derive as _;
+use once_cell as _;
+use proptest as _;
+use regex as _;
+#[cfg(feature = "serde")]
+use serde as _;
+use serde_json as _;
+
+#[global_allocator]
+static GLOBAL: &StatsAlloc<System> = &INSTRUMENTED_SYSTEM;
+
+struct Entry {
+    title: String,
+    needle: String,
+    views: i64,
+    rating: f64,
+    active: bool,
+}
+
+impl Record for Entry {
+    fn get(&self, field: &Field) -> Option<Value<'_>> {
+        match field.name() {
+            "title" => Some(Value::Text(Cow::Borrowed(&self.title))),
+            "needle" => Some(Value::Text(Cow::Borrowed(&self.needle))),
+            "views" => Some(Value::Int(self.views)),
+            "rating" => Some(Value::Float(self.rating)),
+            "active" => Some(Value::Bool(self.active)),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn test_compiled_filter_matches_without_allocations() {
+    let scenarios = vec![
+        ("ASCII literal", "title ~ 'WORLD'", true),
+        ("non-ASCII literal", "title ~ 'ÉTÉ'", true),
+        ("non-ASCII literal mismatch", "title ~ 'hiver'", false),
+        ("field pattern", "title ~ needle", true),
+        ("negated field pattern", "title !~ needle", false),
+        ("integer operand", "views ~ 23", true),
+        ("float operand", "rating ~ '.5'", true),
+        ("number mismatch", "rating ~ 1 || views ~ rating", false),
+        ("bool operand", "active ~ 'RU'", true),
+        ("missing field", "missing ~ 'a'", false),
+        (
+            "mixed",
+            "views > 100 && (title ~ 'été' || active = false)",
+            true,
+        ),
+    ];
+
+    let entry = Entry {
+        title: "Hello World, ÉTÉ Κόσμε".to_string(),
+        needle: "κΌΣΜΕ".to_string(),
+        views: 1234,
+        rating: 4.5,
+        active: true,
+    };
+
+    for (name, text, expected) in scenarios {
+        let compiled = filter::compile(&fexpr::parse(text).unwrap()).unwrap();
+
+        let region = Region::new(GLOBAL);
+        let result = compiled.matches(&entry);
+        let allocations = region.change().allocations;
+
+        assert!(
+            result == expected,
+            "({}) Expected {}, got {}",
+            name,
+            expected,
+            result
+        );
+        assert!(
+            allocations == 0,
+            "({}) Expected no allocations, got {}",
+            name,
+            allocations
+        );
+    }
+}